@@ -0,0 +1,144 @@
+use crate::controller::*;
+use crate::Projection;
+use nalgebra_glm as glm;
+
+/// Free rotation around a target point, the model follows the cursor as if it were a ball
+/// under the mouse.
+///
+/// Drag with the left button to rotate, with the middle or right button to move the target, and
+/// scroll to move closer. Unlike [`crate::OrbitController`] there is no fixed up axis, so the
+/// model can be turned upside down. Works best with a visible cursor, under
+/// `winman::CursorMode::Relative` drags start from the centre of the window.
+pub struct ArcballController {
+    target: glm::Vec3,
+    distance: f32,
+    orientation: glm::Quat,
+    projection: Projection,
+}
+
+impl ArcballController {
+    /// Starts looking at `target` along -Z.
+    pub fn new(target: &[f32; 3], distance: f32) -> Self {
+        ArcballController {
+            target: glm::make_vec3(target),
+            distance,
+            orientation: glm::quat_identity(),
+            projection: Projection::default(),
+        }
+    }
+
+    pub fn target(&self) -> [f32; 3] {
+        self.target.into()
+    }
+
+    pub fn set_target(&mut self, target: &[f32; 3]) {
+        self.target = glm::make_vec3(target);
+    }
+
+    pub fn projection(&self) -> &Projection {
+        &self.projection
+    }
+
+    pub fn set_projection(&mut self, projection: Projection) {
+        self.projection = projection;
+    }
+
+    pub fn distance(&self) -> f32 {
+        self.distance
+    }
+
+    pub fn set_distance(&mut self, distance: f32) {
+        self.distance = distance.max(0.01);
+    }
+
+    /// Rotation from world space into view space, around the target.
+    pub fn orientation(&self) -> glm::Quat {
+        self.orientation
+    }
+
+    pub fn set_orientation(&mut self, orientation: &glm::Quat) {
+        self.orientation = glm::quat_normalize(orientation);
+    }
+
+    /// Rotates a vector from view space into world space.
+    fn to_world(&self, v: &glm::Vec3) -> glm::Vec3 {
+        glm::quat_rotate_vec3(&glm::quat_inverse(&self.orientation), v)
+    }
+
+    fn drag(&mut self, window_size: (u32, u32), from: (f32, f32), to: (f32, f32)) {
+        let from = project_to_ball(window_size, from);
+        let to = project_to_ball(window_size, to);
+        let axis = glm::cross(&from, &to);
+        if glm::length(&axis) < 1e-6 {
+            return;
+        }
+        let angle = glm::dot(&from, &to).clamp(-1.0, 1.0).acos();
+        let rotation = glm::quat_angle_axis(angle, &glm::normalize(&axis));
+        self.orientation = glm::quat_normalize(&(rotation * self.orientation));
+    }
+}
+
+/// Holroyd's arcball, a sphere filling the smaller window dimension joined to a hyperbolic
+/// sheet outside it so dragging past the edge keeps rotating smoothly.
+fn project_to_ball(window_size: (u32, u32), (x, y): (f32, f32)) -> glm::Vec3 {
+    let radius = window_size.0.min(window_size.1).max(1) as f32 / 2.0;
+    let x = (x - window_size.0 as f32 / 2.0) / radius;
+    let y = (window_size.1 as f32 / 2.0 - y) / radius;
+    let d2 = x * x + y * y;
+    let z = if d2 <= 0.5 {
+        (1.0 - d2).sqrt()
+    } else {
+        0.5 / d2.sqrt()
+    };
+    glm::normalize(&glm::vec3(x, y, z))
+}
+
+impl CameraController for ArcballController {
+    fn process_events(
+        &mut self,
+        window_size: (u32, u32),
+        _seconds_since_last_frame: f32,
+        events: &[winman::Event],
+        input_state: &winman::InputState,
+    ) {
+        for event in events {
+            match event {
+                winman::Event::MouseMotion { x, y, x_rel, y_rel } => {
+                    let (x_rel, y_rel) = input_state.to_pixels((*x_rel, *y_rel));
+                    if input_state.is_button_held(winman::MouseButton::Left) {
+                        // The hidden cursor's position means nothing in relative mode
+                        let (x, y) = match input_state.cursor_mode() {
+                            winman::CursorMode::Relative => (
+                                window_size.0 as f32 / 2.0 + x_rel,
+                                window_size.1 as f32 / 2.0 + y_rel,
+                            ),
+                            _ => input_state.to_pixels((*x, *y)),
+                        };
+                        self.drag(window_size, (x - x_rel, y - y_rel), (x, y));
+                    } else if is_panning(input_state) {
+                        let scale = self.projection.units_per_pixel(window_size, self.distance);
+                        self.target += scale * self.to_world(&glm::vec3(-x_rel, y_rel, 0.0));
+                    }
+                }
+                winman::Event::MouseScroll { y, .. } => {
+                    self.distance = scroll_distance(self.distance, *y);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn calculate_view(&self) -> glm::Mat4 {
+        glm::translation(&glm::vec3(0.0, 0.0, -self.distance))
+            * glm::quat_to_mat4(&self.orientation)
+            * glm::translation(&-self.target)
+    }
+
+    fn calculate_projection(&self, window_size: (u32, u32)) -> glm::Mat4 {
+        self.projection.calculate(window_size)
+    }
+
+    fn get_position(&self) -> [f32; 3] {
+        (self.target + self.to_world(&glm::vec3(0.0, 0.0, self.distance))).into()
+    }
+}
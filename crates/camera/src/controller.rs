@@ -0,0 +1,62 @@
+use crate::{Camera, Frustum};
+use nalgebra_glm as glm;
+
+/// Turns window input into a view and projection, so examples can swap how the user moves
+/// around without changing how they render.
+pub trait CameraController {
+    fn process_events(
+        &mut self,
+        window_size: (u32, u32),
+        seconds_since_last_frame: f32,
+        events: &[winman::Event],
+        input_state: &winman::InputState,
+    );
+
+    fn calculate_view(&self) -> glm::Mat4;
+
+    fn calculate_projection(&self, window_size: (u32, u32)) -> glm::Mat4;
+
+    /// Eye position in world space.
+    fn get_position(&self) -> [f32; 3];
+
+    /// World space frustum planes.
+    fn calculate_frustum(&self, window_size: (u32, u32)) -> Frustum {
+        Frustum::from_matrix(&(self.calculate_projection(window_size) * self.calculate_view()))
+    }
+}
+
+impl CameraController for Camera {
+    fn process_events(
+        &mut self,
+        _window_size: (u32, u32),
+        seconds_since_last_frame: f32,
+        events: &[winman::Event],
+        input_state: &winman::InputState,
+    ) {
+        crate::process_events(self, seconds_since_last_frame, events, input_state);
+    }
+
+    fn calculate_view(&self) -> glm::Mat4 {
+        Camera::calculate_view(self)
+    }
+
+    fn calculate_projection(&self, window_size: (u32, u32)) -> glm::Mat4 {
+        Camera::calculate_projection(self, window_size)
+    }
+
+    fn get_position(&self) -> [f32; 3] {
+        Camera::get_position(self)
+    }
+}
+
+/// Scales `distance` by 10% per scroll step, towards the target when scrolling away from the
+/// user.
+pub(crate) fn scroll_distance(distance: f32, scroll: f32) -> f32 {
+    (distance * 0.9f32.powf(scroll)).max(0.01)
+}
+
+/// Left button rotates, middle or right button pans.
+pub(crate) fn is_panning(input_state: &winman::InputState) -> bool {
+    input_state.is_button_held(winman::MouseButton::Middle)
+        || input_state.is_button_held(winman::MouseButton::Right)
+}
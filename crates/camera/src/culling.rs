@@ -0,0 +1,143 @@
+use nalgebra_glm as glm;
+
+/// Points with a non-negative signed distance are on the inside.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Plane {
+    /// Unit length.
+    pub normal: glm::Vec3,
+    pub distance: f32,
+}
+
+impl Plane {
+    /// From `ax + by + cz + d >= 0`, normalising so distances are in world units.
+    fn from_coefficients(coefficients: glm::Vec4) -> Self {
+        let length = glm::length(&coefficients.xyz());
+        Plane {
+            normal: coefficients.xyz() / length,
+            distance: coefficients.w / length,
+        }
+    }
+
+    pub fn signed_distance(&self, point: &glm::Vec3) -> f32 {
+        glm::dot(&self.normal, point) + self.distance
+    }
+}
+
+/// The six planes bounding what a projection can see, in the space of the matrix they were
+/// extracted from.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Frustum {
+    /// Left, right, bottom, top, near and far.
+    pub planes: [Plane; 6],
+}
+
+impl Frustum {
+    /// Extracts the planes from a clip space matrix, e.g. `projection * view` for world space
+    /// or `projection * view * model` for testing a model's own bounds.
+    pub fn from_matrix(clip_from_space: &glm::Mat4) -> Self {
+        let row = |i| clip_from_space.row(i).transpose();
+        let (x, y, z, w) = (row(0), row(1), row(2), row(3));
+        Frustum {
+            planes: [w + x, w - x, w + y, w - y, w + z, w - z].map(Plane::from_coefficients),
+        }
+    }
+
+    /// Conservative, a sphere near a corner may pass without being visible.
+    pub fn intersects_sphere(&self, center: &[f32; 3], radius: f32) -> bool {
+        let center = glm::make_vec3(center);
+        self.planes
+            .iter()
+            .all(|plane| plane.signed_distance(&center) >= -radius)
+    }
+
+    /// Conservative, a box near a corner may pass without being visible.
+    pub fn intersects_aabb(&self, min: &[f32; 3], max: &[f32; 3]) -> bool {
+        let (min, max) = (glm::make_vec3(min), glm::make_vec3(max));
+        self.planes.iter().all(|plane| {
+            // The corner furthest along the plane's normal
+            let corner = glm::vec3(
+                if plane.normal.x >= 0.0 { max.x } else { min.x },
+                if plane.normal.y >= 0.0 { max.y } else { min.y },
+                if plane.normal.z >= 0.0 { max.z } else { min.z },
+            );
+            plane.signed_distance(&corner) >= 0.0
+        })
+    }
+}
+
+/// The axis-aligned box around `min` and `max` once transformed by `matrix`, as `(min, max)`.
+pub fn transform_aabb(matrix: &glm::Mat4, min: &[f32; 3], max: &[f32; 3]) -> ([f32; 3], [f32; 3]) {
+    let center = (glm::make_vec3(min) + glm::make_vec3(max)) / 2.0;
+    let half_extent = (glm::make_vec3(max) - glm::make_vec3(min)) / 2.0;
+    let center = (matrix * center.push(1.0)).xyz();
+    let linear = glm::mat4_to_mat3(matrix);
+    let half_extent = linear.abs() * half_extent;
+    ((center - half_extent).into(), (center + half_extent).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frustum() -> Frustum {
+        let projection = glm::perspective(1.0, 90f32.to_radians(), 1.0, 100.0);
+        let view = glm::look_at(
+            &glm::vec3(0.0, 0.0, 5.0),
+            &glm::Vec3::zeros(),
+            &glm::Vec3::y(),
+        );
+        Frustum::from_matrix(&(projection * view))
+    }
+
+    #[test]
+    fn planes_are_normalised() {
+        for plane in frustum().planes {
+            assert!((glm::length(&plane.normal) - 1.0).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn near_and_far_planes_bound_the_view_direction() {
+        let [.., near, far] = frustum().planes;
+        // The eye is at z = 5 looking down -z, with near and far at z = 4 and z = -95
+        assert!((near.signed_distance(&glm::vec3(0.0, 0.0, 4.0))).abs() < 1e-3);
+        assert!((far.signed_distance(&glm::vec3(0.0, 0.0, -95.0))).abs() < 1e-2);
+        assert!(near.signed_distance(&glm::vec3(0.0, 0.0, 4.5)) < 0.0);
+        assert!(far.signed_distance(&glm::vec3(0.0, 0.0, -96.0)) < 0.0);
+    }
+
+    #[test]
+    fn spheres() {
+        let frustum = frustum();
+        assert!(frustum.intersects_sphere(&[0.0, 0.0, 0.0], 1.0));
+        // Behind the eye
+        assert!(!frustum.intersects_sphere(&[0.0, 0.0, 10.0], 1.0));
+        // With a 90° field of view the side planes are at |x| = 5 at the origin
+        assert!(!frustum.intersects_sphere(&[7.0, 0.0, 0.0], 1.0));
+        assert!(frustum.intersects_sphere(&[5.5, 0.0, 0.0], 1.0));
+    }
+
+    #[test]
+    fn boxes() {
+        let frustum = frustum();
+        assert!(frustum.intersects_aabb(&[-1.0; 3], &[1.0; 3]));
+        assert!(!frustum.intersects_aabb(&[-1.0, -1.0, 200.0], &[1.0, 1.0, 201.0]));
+        assert!(!frustum.intersects_aabb(&[8.0, -1.0, -1.0], &[9.0, 1.0, 1.0]));
+        // Straddling a side plane
+        assert!(frustum.intersects_aabb(&[4.0, -1.0, -1.0], &[7.0, 1.0, 1.0]));
+    }
+
+    #[test]
+    fn transformed_box_contains_rotated_corners() {
+        let matrix = glm::translation(&glm::vec3(10.0, 0.0, 0.0))
+            * glm::rotation(45f32.to_radians(), &glm::Vec3::z());
+        let (min, max) = transform_aabb(&matrix, &[-1.0; 3], &[1.0; 3]);
+        let half_diagonal = 2f32.sqrt();
+        let expected_min = [10.0 - half_diagonal, -half_diagonal, -1.0];
+        let expected_max = [10.0 + half_diagonal, half_diagonal, 1.0];
+        for axis in 0..3 {
+            assert!((min[axis] - expected_min[axis]).abs() < 1e-5);
+            assert!((max[axis] - expected_max[axis]).abs() < 1e-5);
+        }
+    }
+}
@@ -1,26 +1,193 @@
+mod arcball;
+mod controller;
+mod culling;
+mod orbit;
+mod pan_zoom;
+mod path;
+mod projection;
+mod ray;
+
+pub use arcball::*;
+pub use controller::*;
+pub use culling::*;
+pub use orbit::*;
+pub use pan_zoom::*;
+pub use path::*;
+pub use projection::*;
+pub use ray::*;
+
 use nalgebra_glm as glm;
 
-#[derive(Default)]
-pub struct Controls {
-    left: f32,
-    right: f32,
-    forward: f32,
-    backward: f32,
+/// Keys that move the camera, in camera space apart from `up` and `down`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct KeyBindings {
+    pub forward: winman::Keycode,
+    pub back: winman::Keycode,
+    pub left: winman::Keycode,
+    pub right: winman::Keycode,
+    pub up: winman::Keycode,
+    pub down: winman::Keycode,
+    pub sprint: winman::Keycode,
+    /// Only used in [`OrientationMode::Free`].
+    pub roll_left: winman::Keycode,
+    pub roll_right: winman::Keycode,
+}
+
+impl Default for KeyBindings {
+    /// WASD, with E and Q moving up and down, left shift to sprint and Z and X to roll.
+    fn default() -> Self {
+        KeyBindings {
+            forward: winman::Keycode::W,
+            back: winman::Keycode::S,
+            left: winman::Keycode::A,
+            right: winman::Keycode::D,
+            up: winman::Keycode::E,
+            down: winman::Keycode::Q,
+            sprint: winman::Keycode::LShift,
+            roll_left: winman::Keycode::Z,
+            roll_right: winman::Keycode::X,
+        }
+    }
+}
+
+/// How the camera accelerates and slows down, independent of the frame rate.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Movement {
+    /// Units per second squared at full input.
+    pub acceleration: f32,
+    /// Rate at which velocity decays, it shrinks by a factor of `e` every `1 / damping` seconds.
+    pub damping: f32,
+    /// Units per second.
+    pub max_speed: f32,
+    /// Scales both `acceleration` and `max_speed` while sprinting.
+    pub sprint_multiplier: f32,
+    /// Degrees per second, only used in [`OrientationMode::Free`].
+    pub roll_speed: f32,
+    pub bindings: KeyBindings,
+}
+
+impl Default for Movement {
+    fn default() -> Self {
+        Movement {
+            acceleration: 70.0,
+            damping: 1.8,
+            max_speed: 40.0,
+            sprint_multiplier: 2.5,
+            roll_speed: 90.0,
+            bindings: KeyBindings::default(),
+        }
+    }
+}
+
+/// Force from the held movement keys, in camera space with `y` along the world up unless the
+/// camera is in [`OrientationMode::Free`].
+pub fn calculate_force(
+    input_state: &winman::InputState,
+    bindings: &KeyBindings,
+    acceleration: f32,
+) -> glm::Vec3 {
+    let held = |keycode| {
+        if input_state.is_key_held(keycode) {
+            acceleration
+        } else {
+            0.0
+        }
+    };
+    glm::vec3(
+        held(bindings.right) - held(bindings.left),
+        held(bindings.up) - held(bindings.down),
+        held(bindings.forward) - held(bindings.back),
+    )
+}
+
+/// Whether the sprint key or the first controller's left stick is held.
+pub fn is_sprinting(input_state: &winman::InputState, bindings: &KeyBindings) -> bool {
+    input_state.is_key_held(bindings.sprint)
+        || input_state.controllers().first().is_some_and(|&id| {
+            input_state.is_controller_button_held(id, winman::ControllerButton::LeftStick)
+        })
 }
 
-impl Controls {
-    pub fn calculate_force(&self) -> glm::Vec3 {
-        glm::vec3(self.right - self.left, 0.0, self.forward - self.backward)
+/// Roll from the held roll keys or the first controller's shoulder buttons, positive to the
+/// right.
+pub fn calculate_roll(input_state: &winman::InputState, bindings: &KeyBindings) -> f32 {
+    let held = |keycode| f32::from(u8::from(input_state.is_key_held(keycode)));
+    let mut roll = held(bindings.roll_right) - held(bindings.roll_left);
+    if let Some(&id) = input_state.controllers().first() {
+        let held = |button| f32::from(u8::from(input_state.is_controller_button_held(id, button)));
+        roll += held(winman::ControllerButton::RightShoulder)
+            - held(winman::ControllerButton::LeftShoulder);
     }
+    roll.clamp(-1.0, 1.0)
+}
+
+/// Degrees per second the right stick turns the camera at full deflection.
+const CONTROLLER_LOOK_SPEED: f32 = 120.0;
+
+/// Force from the first controller's left stick, with the triggers moving down and up, in
+/// camera space.
+pub fn calculate_controller_force(
+    input_state: &winman::InputState,
+    acceleration: f32,
+) -> glm::Vec3 {
+    let Some(&id) = input_state.controllers().first() else {
+        return glm::Vec3::zeros();
+    };
+    let axis = |axis| input_state.controller_axis(id, axis);
+    acceleration
+        * glm::vec3(
+            axis(winman::ControllerAxis::LeftX),
+            axis(winman::ControllerAxis::TriggerRight) - axis(winman::ControllerAxis::TriggerLeft),
+            -axis(winman::ControllerAxis::LeftY),
+        )
+}
+
+/// Turns the camera with the first controller's right stick, `look_speed` in degrees per second.
+pub fn process_controller_look(
+    camera: &mut Camera,
+    look_speed: f32,
+    seconds_since_last_frame: f32,
+    input_state: &winman::InputState,
+) {
+    let Some(&id) = input_state.controllers().first() else {
+        return;
+    };
+    let turn = look_speed * seconds_since_last_frame;
+    camera.move_orientation(
+        turn * input_state.controller_axis(id, winman::ControllerAxis::RightX),
+        turn * input_state.controller_axis(id, winman::ControllerAxis::RightY),
+    );
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum OrientationMode {
+    /// Yaw around the world up axis with pitch limited to ±89°, the camera never rolls.
+    YawPitch,
+    /// Yaw and pitch around the camera's own axes, with roll, so it can loop and fly upside
+    /// down. Vertical movement follows the camera's up axis.
+    Free,
+}
+
+/// An orientation the camera is slerping towards.
+struct Transition {
+    from: glm::Quat,
+    to: glm::Quat,
+    seconds: f32,
+    elapsed: f32,
 }
 
 pub struct Camera {
     position: glm::Vec3,
+    /// Rotates camera space, looking down -Z with +Y up, into world space.
+    rotation: glm::Quat,
     front: glm::Vec3,
     up: glm::Vec3,
     right: glm::Vec3,
     velocity: glm::Vec3,
-    fov: f32,
+    movement: Movement,
+    mode: OrientationMode,
+    transition: Option<Transition>,
+    projection: Projection,
     yaw: f32,
     pitch: f32,
 }
@@ -29,26 +196,39 @@ impl Camera {
     pub fn new() -> Self {
         Camera {
             position: glm::vec3(0.0, 0.0, 0.0),
+            rotation: glm::quat_identity(),
             front: glm::vec3(0.0, 0.0, -1.0),
             up: glm::vec3(0.0, 1.0, 0.0),
             right: glm::vec3(1.0, 0.0, 0.0),
             velocity: glm::vec3(0.0, 0.0, 0.0),
-            fov: 45.0,
+            movement: Movement::default(),
+            mode: OrientationMode::YawPitch,
+            transition: None,
+            projection: Projection::default(),
             yaw: -90.0,
             pitch: 0.0,
         }
     }
-
     pub fn calculate_view(&self) -> glm::Mat4 {
         glm::look_at(&self.position, &(self.position + self.front), &self.up)
     }
 
     pub fn calculate_projection(&self, window_size: (u32, u32)) -> glm::Mat4 {
-        glm::perspective(
-            window_size.0 as f32 / window_size.1 as f32,
-            self.fov.to_radians(),
-            0.1,
-            1000.0,
+        self.projection.calculate(window_size)
+    }
+
+    /// World space frustum planes.
+    pub fn calculate_frustum(&self, window_size: (u32, u32)) -> Frustum {
+        Frustum::from_matrix(&(self.calculate_projection(window_size) * self.calculate_view()))
+    }
+
+    /// World space ray through a cursor position in pixels, for picking, see [`screen_ray`].
+    pub fn screen_ray(&self, cursor_position: (f32, f32), window_size: (u32, u32)) -> Ray {
+        screen_ray(
+            &self.calculate_view(),
+            &self.projection,
+            cursor_position,
+            window_size,
         )
     }
 
@@ -64,62 +244,211 @@ impl Camera {
         [self.front.x, self.front.y, self.front.z]
     }
 
+    pub fn get_up(&self) -> [f32; 3] {
+        [self.up.x, self.up.y, self.up.z]
+    }
+
+    pub fn orientation_mode(&self) -> OrientationMode {
+        self.mode
+    }
+
+    /// Switching back to [`OrientationMode::YawPitch`] keeps the view direction but drops any
+    /// roll.
+    pub fn set_orientation_mode(&mut self, mode: OrientationMode) {
+        self.mode = mode;
+        self.set_rotation(self.rotation);
+    }
+
+    /// Rotation from camera space, looking down -Z with +Y up, into world space.
+    pub fn orientation(&self) -> glm::Quat {
+        self.rotation
+    }
+
+    /// Cancels any transition, in [`OrientationMode::YawPitch`] roll is dropped.
+    pub fn set_orientation(&mut self, orientation: &glm::Quat) {
+        self.transition = None;
+        self.set_rotation(*orientation);
+    }
+
+    /// Slerps to `orientation` over `seconds`, as time is passed to [`Camera::update`]. Turning
+    /// the camera before it arrives cancels the transition.
+    pub fn transition_to(&mut self, orientation: &glm::Quat, seconds: f32) {
+        self.transition = Some(Transition {
+            from: self.rotation,
+            to: glm::quat_normalize(orientation),
+            seconds: seconds.max(f32::EPSILON),
+            elapsed: 0.0,
+        });
+    }
+
+    pub fn is_transitioning(&self) -> bool {
+        self.transition.is_some()
+    }
+
+    /// Advances a transition started with [`Camera::transition_to`].
+    pub fn update(&mut self, seconds_since_last_update: f32) {
+        let Some(transition) = &mut self.transition else {
+            return;
+        };
+        transition.elapsed += seconds_since_last_update;
+        let t = (transition.elapsed / transition.seconds).min(1.0);
+        let rotation = glm::quat_slerp(&transition.from, &transition.to, t);
+        if t >= 1.0 {
+            self.transition = None;
+        }
+        self.set_rotation(rotation);
+    }
+
+    pub fn movement(&self) -> &Movement {
+        &self.movement
+    }
+
+    pub fn set_movement(&mut self, movement: Movement) {
+        self.movement = movement;
+    }
+
+    /// Integrates `input`, a force in camera space scaled by the movement acceleration, over
+    /// `seconds_since_last_update`.
     pub fn move_position(
         &mut self,
-        acceleration: glm::Vec3,
-        drag: f32,
+        input: glm::Vec3,
+        sprinting: bool,
         seconds_since_last_update: f32,
     ) {
-        self.velocity *= drag;
-        self.velocity += seconds_since_last_update * acceleration;
+        let boost = if sprinting {
+            self.movement.sprint_multiplier
+        } else {
+            1.0
+        };
+        self.velocity *= (-self.movement.damping * seconds_since_last_update).exp();
+        self.velocity += seconds_since_last_update * boost * input;
+        let max_speed = boost * self.movement.max_speed;
+        if glm::length(&self.velocity) > max_speed {
+            self.velocity = glm::normalize(&self.velocity) * max_speed;
+        }
         self.position += seconds_since_last_update * self.velocity.x * self.right;
-        self.position += seconds_since_last_update * self.velocity.y * self.up;
+        let up = match self.mode {
+            OrientationMode::YawPitch => glm::Vec3::y(),
+            OrientationMode::Free => self.up,
+        };
+        self.position += seconds_since_last_update * self.velocity.y * up;
         self.position += seconds_since_last_update * self.velocity.z * self.front;
     }
 
+    /// Turns right by `x_rel` and down by `y_rel` degrees.
     pub fn move_orientation(&mut self, x_rel: f32, y_rel: f32) {
-        self.yaw += x_rel;
-        self.pitch = (self.pitch - y_rel).clamp(-89.0, 89.0);
-        self.front = glm::normalize(&glm::vec3(
-            self.yaw.to_radians().cos() * self.pitch.to_radians().cos(),
-            self.pitch.to_radians().sin(),
-            self.yaw.to_radians().sin() * self.pitch.to_radians().cos(),
-        ));
-        self.right = glm::normalize(&glm::cross(&self.front, &self.up));
+        if x_rel == 0.0 && y_rel == 0.0 {
+            return;
+        }
+        self.transition = None;
+        match self.mode {
+            OrientationMode::YawPitch => {
+                self.yaw += x_rel;
+                self.pitch = (self.pitch - y_rel).clamp(-89.0, 89.0);
+                self.rotation = yaw_pitch_rotation(self.yaw, self.pitch);
+                self.update_axes();
+            }
+            OrientationMode::Free => {
+                self.set_rotation(
+                    self.rotation
+                        * glm::quat_angle_axis(-x_rel.to_radians(), &glm::Vec3::y())
+                        * glm::quat_angle_axis(-y_rel.to_radians(), &glm::Vec3::x()),
+                );
+            }
+        }
+    }
+
+    /// Rolls clockwise, as seen by the camera, by `degrees`. Ignored in
+    /// [`OrientationMode::YawPitch`].
+    pub fn roll(&mut self, degrees: f32) {
+        if self.mode == OrientationMode::Free && degrees != 0.0 {
+            self.transition = None;
+            self.set_rotation(
+                self.rotation * glm::quat_angle_axis(degrees.to_radians(), &-glm::Vec3::z()),
+            );
+        }
+    }
+
+    fn set_rotation(&mut self, rotation: glm::Quat) {
+        match self.mode {
+            OrientationMode::YawPitch => {
+                let front = glm::quat_rotate_vec3(&rotation, &-glm::Vec3::z());
+                self.yaw = front.z.atan2(front.x).to_degrees();
+                self.pitch = front
+                    .y
+                    .clamp(-1.0, 1.0)
+                    .asin()
+                    .to_degrees()
+                    .clamp(-89.0, 89.0);
+                self.rotation = yaw_pitch_rotation(self.yaw, self.pitch);
+            }
+            // Renormalise so rounding errors don't build up as small rotations are applied
+            OrientationMode::Free => self.rotation = glm::quat_normalize(&rotation),
+        }
+        self.update_axes();
+    }
+
+    fn update_axes(&mut self) {
+        self.front = glm::quat_rotate_vec3(&self.rotation, &-glm::Vec3::z());
+        self.up = glm::quat_rotate_vec3(&self.rotation, &glm::Vec3::y());
+        self.right = glm::quat_rotate_vec3(&self.rotation, &glm::Vec3::x());
+    }
+
+    pub fn projection(&self) -> &Projection {
+        &self.projection
+    }
+
+    pub fn set_projection(&mut self, projection: Projection) {
+        self.projection = projection;
     }
 
     pub fn zoom(&mut self, offset: f32, seconds_since_last_update: f32) {
-        self.fov = (self.fov - offset * seconds_since_last_update).clamp(1.0, 75.0);
+        self.projection.zoom(offset * seconds_since_last_update);
     }
 }
 
+/// Mouse and controller look, scroll to zoom, and movement from the camera's [`Movement`].
 pub fn process_events(
     camera: &mut Camera,
-    controls: &mut Controls,
-    acceleration: f32,
-    drag: f32,
     seconds_since_last_frame: f32,
     events: &[winman::Event],
+    input_state: &winman::InputState,
 ) {
     for event in events {
         match event {
-            winman::Event::KeyUp(winman::Keycode::W) => controls.forward = 0.0,
-            winman::Event::KeyUp(winman::Keycode::S) => controls.backward = 0.0,
-            winman::Event::KeyUp(winman::Keycode::A) => controls.left = 0.0,
-            winman::Event::KeyUp(winman::Keycode::D) => controls.right = 0.0,
-            winman::Event::KeyUp(_) => {}
-            winman::Event::KeyDown(winman::Keycode::W) => controls.forward = acceleration,
-            winman::Event::KeyDown(winman::Keycode::S) => controls.backward = acceleration,
-            winman::Event::KeyDown(winman::Keycode::A) => controls.left = acceleration,
-            winman::Event::KeyDown(winman::Keycode::D) => controls.right = acceleration,
-            winman::Event::KeyDown(_) => {}
-            winman::Event::MousePosition(xrel, yrel) => {
-                camera.move_orientation(*xrel * 0.05, *yrel * 0.05)
+            winman::Event::MouseMotion { x_rel, y_rel, .. } => {
+                camera.move_orientation(*x_rel * 0.05, *y_rel * 0.05)
             }
-            winman::Event::MouseScroll(offset) => {
-                camera.zoom(*offset * 10.0, seconds_since_last_frame)
+            winman::Event::MouseScroll { y, .. } => {
+                camera.zoom(*y * 10.0, seconds_since_last_frame)
             }
+            _ => {}
         }
     }
-    camera.move_position(controls.calculate_force(), drag, seconds_since_last_frame);
+    process_controller_look(
+        camera,
+        CONTROLLER_LOOK_SPEED,
+        seconds_since_last_frame,
+        input_state,
+    );
+    let movement = *camera.movement();
+    camera.roll(
+        movement.roll_speed
+            * seconds_since_last_frame
+            * calculate_roll(input_state, &movement.bindings),
+    );
+    camera.update(seconds_since_last_frame);
+    camera.move_position(
+        calculate_force(input_state, &movement.bindings, movement.acceleration)
+            + calculate_controller_force(input_state, movement.acceleration),
+        is_sprinting(input_state, &movement.bindings),
+        seconds_since_last_frame,
+    );
+}
+
+/// Camera space rotation with the front at `yaw` degrees from +X towards +Z, raised by `pitch`
+/// degrees, and no roll.
+fn yaw_pitch_rotation(yaw: f32, pitch: f32) -> glm::Quat {
+    glm::quat_angle_axis(-(yaw + 90.0).to_radians(), &glm::Vec3::y())
+        * glm::quat_angle_axis(pitch.to_radians(), &glm::Vec3::x())
 }
@@ -0,0 +1,127 @@
+use crate::controller::*;
+use crate::Projection;
+use nalgebra_glm as glm;
+
+/// Degrees the view turns per pixel dragged.
+const ROTATE_SPEED: f32 = 0.3;
+
+/// Turntable camera circling a target point, keeping the world up axis upright.
+///
+/// Drag with the left button to orbit, with the middle or right button to move the target, and
+/// scroll to move closer. The first controller's right stick also orbits.
+pub struct OrbitController {
+    target: glm::Vec3,
+    distance: f32,
+    yaw: f32,
+    pitch: f32,
+    projection: Projection,
+}
+
+impl OrbitController {
+    /// Starts looking at `target` along -Z.
+    pub fn new(target: &[f32; 3], distance: f32) -> Self {
+        OrbitController {
+            target: glm::make_vec3(target),
+            distance,
+            yaw: 0.0,
+            pitch: 0.0,
+            projection: Projection::default(),
+        }
+    }
+
+    pub fn target(&self) -> [f32; 3] {
+        self.target.into()
+    }
+
+    pub fn set_target(&mut self, target: &[f32; 3]) {
+        self.target = glm::make_vec3(target);
+    }
+
+    pub fn projection(&self) -> &Projection {
+        &self.projection
+    }
+
+    pub fn set_projection(&mut self, projection: Projection) {
+        self.projection = projection;
+    }
+
+    pub fn distance(&self) -> f32 {
+        self.distance
+    }
+
+    pub fn set_distance(&mut self, distance: f32) {
+        self.distance = distance.max(0.01);
+    }
+
+    /// Yaw around the world up axis and pitch above the horizon, in degrees.
+    pub fn set_angles(&mut self, yaw: f32, pitch: f32) {
+        self.yaw = yaw;
+        self.pitch = pitch.clamp(-89.0, 89.0);
+    }
+
+    fn eye(&self) -> glm::Vec3 {
+        let (yaw, pitch) = (self.yaw.to_radians(), self.pitch.to_radians());
+        self.target
+            + self.distance
+                * glm::vec3(
+                    pitch.cos() * yaw.sin(),
+                    pitch.sin(),
+                    pitch.cos() * yaw.cos(),
+                )
+    }
+
+    fn rotate(&mut self, x: f32, y: f32) {
+        self.set_angles(self.yaw - x, self.pitch + y);
+    }
+}
+
+impl CameraController for OrbitController {
+    fn process_events(
+        &mut self,
+        window_size: (u32, u32),
+        seconds_since_last_frame: f32,
+        events: &[winman::Event],
+        input_state: &winman::InputState,
+    ) {
+        for event in events {
+            match event {
+                winman::Event::MouseMotion { x_rel, y_rel, .. } => {
+                    if input_state.is_button_held(winman::MouseButton::Left) {
+                        self.rotate(*x_rel * ROTATE_SPEED, *y_rel * ROTATE_SPEED);
+                    } else if is_panning(input_state) {
+                        let (x_rel, y_rel) = input_state.to_pixels((*x_rel, *y_rel));
+                        let front = glm::normalize(&(self.target - self.eye()));
+                        let right = glm::normalize(&glm::cross(&front, &glm::Vec3::y()));
+                        let up = glm::cross(&right, &front);
+                        let scale = self.projection.units_per_pixel(window_size, self.distance);
+                        self.target += scale * (y_rel * up - x_rel * right);
+                    }
+                }
+                winman::Event::MouseScroll { y, .. } => {
+                    self.distance = scroll_distance(self.distance, *y);
+                }
+                _ => {}
+            }
+        }
+
+        if let Some(&id) = input_state.controllers().first() {
+            let turn = crate::CONTROLLER_LOOK_SPEED * seconds_since_last_frame;
+            self.rotate(
+                turn * input_state.controller_axis(id, winman::ControllerAxis::RightX),
+                turn * input_state.controller_axis(id, winman::ControllerAxis::RightY),
+            );
+        }
+    }
+
+    fn calculate_view(&self) -> glm::Mat4 {
+        glm::look_at(&self.eye(), &self.target, &glm::Vec3::y())
+    }
+
+    fn calculate_projection(&self, window_size: (u32, u32)) -> glm::Mat4 {
+        self.projection.calculate(window_size)
+    }
+
+    fn get_position(&self) -> [f32; 3] {
+        self.eye().into()
+    }
+}
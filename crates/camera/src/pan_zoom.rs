@@ -0,0 +1,103 @@
+use crate::CameraController;
+use nalgebra_glm as glm;
+
+/// Orthographic 2D view of the XY plane, for images, plots and tile maps.
+///
+/// Drag with the left or middle button to pan, scroll to zoom towards the cursor.
+pub struct PanZoomController {
+    center: glm::Vec2,
+    /// World units visible from the bottom to the top of the window.
+    height: f32,
+}
+
+impl PanZoomController {
+    pub fn new(center: &[f32; 2], height: f32) -> Self {
+        PanZoomController {
+            center: glm::make_vec2(center),
+            height,
+        }
+    }
+
+    pub fn center(&self) -> [f32; 2] {
+        self.center.into()
+    }
+
+    pub fn set_center(&mut self, center: &[f32; 2]) {
+        self.center = glm::make_vec2(center);
+    }
+
+    pub fn height(&self) -> f32 {
+        self.height
+    }
+
+    pub fn set_height(&mut self, height: f32) {
+        self.height = height.max(1e-4);
+    }
+
+    /// World position under a cursor position in pixels, see `winman::InputState::to_pixels`.
+    pub fn to_world(&self, window_size: (u32, u32), (x, y): (f32, f32)) -> [f32; 2] {
+        let scale = self.units_per_pixel(window_size);
+        let offset = glm::vec2(
+            x - window_size.0 as f32 / 2.0,
+            window_size.1 as f32 / 2.0 - y,
+        );
+        (self.center + scale * offset).into()
+    }
+
+    fn units_per_pixel(&self, window_size: (u32, u32)) -> f32 {
+        self.height / window_size.1.max(1) as f32
+    }
+}
+
+impl CameraController for PanZoomController {
+    fn process_events(
+        &mut self,
+        window_size: (u32, u32),
+        _seconds_since_last_frame: f32,
+        events: &[winman::Event],
+        input_state: &winman::InputState,
+    ) {
+        for event in events {
+            match event {
+                winman::Event::MouseMotion { x_rel, y_rel, .. }
+                    if input_state.is_button_held(winman::MouseButton::Left)
+                        || input_state.is_button_held(winman::MouseButton::Middle) =>
+                {
+                    let (x_rel, y_rel) = input_state.to_pixels((*x_rel, *y_rel));
+                    let scale = self.units_per_pixel(window_size);
+                    self.center += scale * glm::vec2(-x_rel, y_rel);
+                }
+                winman::Event::MouseScroll { y, .. } => {
+                    // Keep the point under the cursor where it is
+                    let cursor = input_state.to_pixels(input_state.cursor_position());
+                    let before = glm::make_vec2(&self.to_world(window_size, cursor));
+                    self.set_height(self.height * 0.9f32.powf(*y));
+                    let after = glm::make_vec2(&self.to_world(window_size, cursor));
+                    self.center += before - after;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn calculate_view(&self) -> glm::Mat4 {
+        glm::translation(&glm::vec3(-self.center.x, -self.center.y, 0.0))
+    }
+
+    fn calculate_projection(&self, window_size: (u32, u32)) -> glm::Mat4 {
+        let half_height = self.height / 2.0;
+        let half_width = half_height * crate::aspect_ratio(window_size);
+        glm::ortho(
+            -half_width,
+            half_width,
+            -half_height,
+            half_height,
+            -1000.0,
+            1000.0,
+        )
+    }
+
+    fn get_position(&self) -> [f32; 3] {
+        [self.center.x, self.center.y, 0.0]
+    }
+}
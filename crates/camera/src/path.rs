@@ -0,0 +1,314 @@
+use crate::Camera;
+use anyhow::Context;
+use nalgebra_glm as glm;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Camera pose at a point in time, in seconds from the start of the path.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Keyframe {
+    pub time: f32,
+    pub position: [f32; 3],
+    /// Rotation from camera space into world space, as `[x, y, z, w]`.
+    pub orientation: [f32; 4],
+}
+
+impl Keyframe {
+    /// The pose of the camera that `view` was made for.
+    pub fn from_view(time: f32, view: &glm::Mat4) -> Self {
+        let world_from_view = glm::inverse(view);
+        let orientation = glm::mat3_to_quat(&glm::mat4_to_mat3(&world_from_view));
+        Keyframe {
+            time,
+            position: world_from_view.column(3).xyz().into(),
+            orientation: orientation.coords.into(),
+        }
+    }
+
+    fn quat(&self) -> glm::Quat {
+        glm::make_quat(&self.orientation)
+    }
+
+    pub fn calculate_view(&self) -> glm::Mat4 {
+        glm::quat_to_mat4(&glm::quat_inverse(&self.quat()))
+            * glm::translation(&-glm::make_vec3(&self.position))
+    }
+
+    /// Moves and turns `camera` to this pose, in [`crate::OrientationMode::YawPitch`] any roll
+    /// is dropped.
+    pub fn apply_to(&self, camera: &mut Camera) {
+        camera.set_position(&self.position);
+        camera.set_orientation(&self.quat());
+    }
+}
+
+/// Keyframes in time order, saved as RON.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct CameraPath {
+    pub keyframes: Vec<Keyframe>,
+}
+
+impl CameraPath {
+    pub fn load_from_file<P: AsRef<Path>>(path: P) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read camera path {}", path.display()))?;
+        ron::from_str(&text)
+            .with_context(|| format!("Failed to parse camera path {}", path.display()))
+    }
+
+    pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> anyhow::Result<()> {
+        let path = path.as_ref();
+        let text = ron::ser::to_string_pretty(self, Default::default())?;
+        std::fs::write(path, text)
+            .with_context(|| format!("Failed to write camera path {}", path.display()))
+    }
+
+    /// Time of the last keyframe.
+    pub fn duration(&self) -> f32 {
+        self.keyframes.last().map_or(0.0, |keyframe| keyframe.time)
+    }
+
+    /// Pose at `time`, clamped to the ends of the path. Positions follow a Catmull-Rom spline
+    /// through the keyframes and orientations are slerped, so the result only depends on
+    /// `time`, not on the frame rate it was recorded at.
+    pub fn sample(&self, time: f32) -> Option<Keyframe> {
+        let keyframes = &self.keyframes;
+        let last = keyframes.len().checked_sub(1)?;
+        let next = keyframes.partition_point(|keyframe| keyframe.time <= time);
+        if next == 0 || next > last {
+            let keyframe = keyframes[next.min(last)];
+            return Some(Keyframe { time, ..keyframe });
+        }
+
+        let (from, to) = (keyframes[next - 1], keyframes[next]);
+        let span = to.time - from.time;
+        let t = if span > 0.0 {
+            (time - from.time) / span
+        } else {
+            1.0
+        };
+        // The ends are repeated so the spline still passes through the first and last keyframes
+        let before = keyframes[next.saturating_sub(2)];
+        let after = keyframes[(next + 1).min(last)];
+        let [p0, p1, p2, p3] =
+            [before, from, to, after].map(|keyframe| glm::make_vec3(&keyframe.position));
+        let position = catmull_rom(&p0, &p1, &p2, &p3, t);
+
+        Some(Keyframe {
+            time,
+            position: position.into(),
+            orientation: glm::quat_slerp(&from.quat(), &to.quat(), t).coords.into(),
+        })
+    }
+}
+
+/// Uniform Catmull-Rom between `p1` and `p2`.
+fn catmull_rom(
+    p0: &glm::Vec3,
+    p1: &glm::Vec3,
+    p2: &glm::Vec3,
+    p3: &glm::Vec3,
+    t: f32,
+) -> glm::Vec3 {
+    let (t2, t3) = (t * t, t * t * t);
+    0.5 * (2.0 * p1
+        + (p2 - p0) * t
+        + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * t2
+        + (3.0 * p1 - p0 - 3.0 * p2 + p3) * t3)
+}
+
+/// Collects a keyframe per frame, or per `interval` seconds for a sparser path.
+pub struct PathRecorder {
+    path: CameraPath,
+    interval: f32,
+    elapsed: f32,
+}
+
+impl PathRecorder {
+    pub fn new(interval: f32) -> Self {
+        Self {
+            path: Default::default(),
+            interval,
+            elapsed: 0.0,
+        }
+    }
+
+    /// Call once per frame with the view being rendered.
+    pub fn record(&mut self, view: &glm::Mat4, seconds_since_last_frame: f32) {
+        if !self.path.keyframes.is_empty() {
+            self.elapsed += seconds_since_last_frame;
+        }
+        let due = self
+            .path
+            .keyframes
+            .last()
+            .is_none_or(|last| self.elapsed - last.time >= self.interval);
+        if due {
+            self.path
+                .keyframes
+                .push(Keyframe::from_view(self.elapsed, view));
+        }
+    }
+
+    pub fn path(&self) -> &CameraPath {
+        &self.path
+    }
+
+    pub fn finish(self) -> CameraPath {
+        self.path
+    }
+}
+
+/// Steps through a [`CameraPath`]. Advancing by a constant time step, rather than the
+/// measured frame time, makes every run render exactly the same frames.
+pub struct PathPlayer {
+    path: CameraPath,
+    time: f32,
+}
+
+impl PathPlayer {
+    pub fn new(path: CameraPath) -> Self {
+        Self { path, time: 0.0 }
+    }
+
+    /// The pose for the current time, then moves on by `seconds`. `None` once past the end.
+    pub fn advance(&mut self, seconds: f32) -> Option<Keyframe> {
+        if self.is_finished() {
+            return None;
+        }
+        let keyframe = self.path.sample(self.time);
+        self.time += seconds;
+        keyframe
+    }
+
+    pub fn path(&self) -> &CameraPath {
+        &self.path
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.time > self.path.duration()
+    }
+
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+
+    pub fn restart(&mut self) {
+        self.time = 0.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keyframe(time: f32, position: [f32; 3]) -> Keyframe {
+        Keyframe {
+            time,
+            position,
+            orientation: [0.0, 0.0, 0.0, 1.0],
+        }
+    }
+
+    fn path() -> CameraPath {
+        CameraPath {
+            keyframes: vec![
+                keyframe(0.0, [0.0, 0.0, 0.0]),
+                keyframe(1.0, [1.0, 0.0, 0.0]),
+                keyframe(2.0, [1.0, 1.0, 0.0]),
+                keyframe(4.0, [0.0, 1.0, 2.0]),
+            ],
+        }
+    }
+
+    fn approx(a: [f32; 3], b: [f32; 3]) -> bool {
+        a.iter().zip(b).all(|(a, b)| (a - b).abs() < 1e-5)
+    }
+
+    #[test]
+    fn sample_passes_through_keyframes() {
+        let path = path();
+        for keyframe in &path.keyframes {
+            let sample = path.sample(keyframe.time).unwrap();
+            assert!(approx(sample.position, keyframe.position));
+        }
+    }
+
+    #[test]
+    fn sample_clamps_to_the_ends() {
+        let path = path();
+        assert!(approx(path.sample(-1.0).unwrap().position, [0.0; 3]));
+        assert!(approx(path.sample(10.0).unwrap().position, [0.0, 1.0, 2.0]));
+        assert_eq!(path.sample(10.0).unwrap().time, 10.0);
+        assert_eq!(CameraPath::default().sample(0.0), None);
+    }
+
+    #[test]
+    fn evenly_spaced_points_on_a_line_interpolate_linearly() {
+        let path = CameraPath {
+            keyframes: (0..4)
+                .map(|i| keyframe(i as f32, [i as f32, 0.0, 0.0]))
+                .collect(),
+        };
+        assert!(approx(path.sample(1.5).unwrap().position, [1.5, 0.0, 0.0]));
+    }
+
+    #[test]
+    fn orientations_are_slerped() {
+        let turned = glm::quat_angle_axis(90f32.to_radians(), &glm::Vec3::y());
+        let path = CameraPath {
+            keyframes: vec![
+                keyframe(0.0, [0.0; 3]),
+                Keyframe {
+                    orientation: turned.coords.into(),
+                    ..keyframe(1.0, [0.0; 3])
+                },
+            ],
+        };
+        let halfway = glm::quat_angle_axis(45f32.to_radians(), &glm::Vec3::y());
+        let sample = path.sample(0.5).unwrap();
+        assert!(glm::quat_dot(&sample.quat(), &halfway).abs() > 1.0 - 1e-5);
+    }
+
+    #[test]
+    fn views_round_trip_through_keyframes() {
+        let view = glm::look_at(
+            &glm::vec3(1.0, 2.0, 3.0),
+            &glm::vec3(0.0, 1.0, 0.0),
+            &glm::Vec3::y(),
+        );
+        let keyframe = Keyframe::from_view(0.0, &view);
+        assert!(approx(keyframe.position, [1.0, 2.0, 3.0]));
+        let difference = keyframe.calculate_view() - view;
+        assert!(difference.iter().all(|value| value.abs() < 1e-5));
+    }
+
+    #[test]
+    fn ron_round_trip() {
+        let path = path();
+        let text = ron::ser::to_string_pretty(&path, Default::default()).unwrap();
+        assert_eq!(ron::from_str::<CameraPath>(&text).unwrap(), path);
+    }
+
+    #[test]
+    fn recorder_respects_the_interval() {
+        let mut recorder = PathRecorder::new(0.5);
+        for _ in 0..9 {
+            recorder.record(&glm::identity(), 0.25);
+        }
+        let times: Vec<f32> = recorder.path().keyframes.iter().map(|k| k.time).collect();
+        assert_eq!(times, [0.0, 0.5, 1.0, 1.5, 2.0]);
+    }
+
+    #[test]
+    fn playback_with_a_fixed_step_is_reproducible() {
+        let play = || {
+            let mut player = PathPlayer::new(path());
+            std::iter::from_fn(|| player.advance(1.0 / 60.0)).collect::<Vec<_>>()
+        };
+        let first = play();
+        assert_eq!(first.len(), 241);
+        assert_eq!(first, play());
+    }
+}
@@ -0,0 +1,228 @@
+use nalgebra_glm as glm;
+
+/// How view space is mapped to clip space. Angles are in degrees.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Projection {
+    /// `fov` is the vertical field of view.
+    Perspective { fov: f32, near: f32, far: f32 },
+    /// Perspective without a far plane, with depth going from 1 at `near` to 0 at infinity.
+    /// Keeps precision at a distance when paired with
+    /// `opengl_sys::set_clip_control(ClipDepth::ZeroToOne)`, a `Greater` depth test and
+    /// clearing depth to 0. Clip control needs a GL 4.5 context or ARB_clip_control, the
+    /// default 3.3 context doesn't have it.
+    ReversedZInfinite { fov: f32, near: f32 },
+    /// Centred on the view axis, `height` in world units with the width following the window.
+    Orthographic { height: f32, near: f32, far: f32 },
+    /// Asymmetric perspective frustum given by its edges on the near plane, for stereo, tiled
+    /// and portal rendering. Ignores the window's aspect ratio.
+    OffAxis {
+        left: f32,
+        right: f32,
+        bottom: f32,
+        top: f32,
+        near: f32,
+        far: f32,
+    },
+}
+
+impl Default for Projection {
+    fn default() -> Self {
+        Projection::perspective(45.0)
+    }
+}
+
+impl Projection {
+    /// Perspective with the near and far planes at 0.1 and 1000.
+    pub fn perspective(fov: f32) -> Self {
+        Projection::Perspective {
+            fov,
+            near: 0.1,
+            far: 1000.0,
+        }
+    }
+
+    pub fn orthographic(height: f32) -> Self {
+        Projection::Orthographic {
+            height,
+            near: -1000.0,
+            far: 1000.0,
+        }
+    }
+
+    pub fn calculate(&self, window_size: (u32, u32)) -> glm::Mat4 {
+        let aspect = aspect_ratio(window_size);
+        match *self {
+            Projection::Perspective { fov, near, far } => {
+                glm::perspective(aspect, fov.to_radians(), near, far)
+            }
+            Projection::ReversedZInfinite { fov, near } => {
+                let focal_length = 1.0 / (fov.to_radians() / 2.0).tan();
+                #[rustfmt::skip]
+                let projection = glm::Mat4::new(
+                    focal_length / aspect, 0.0, 0.0, 0.0,
+                    0.0, focal_length, 0.0, 0.0,
+                    0.0, 0.0, 0.0, near,
+                    0.0, 0.0, -1.0, 0.0,
+                );
+                projection
+            }
+            Projection::Orthographic { height, near, far } => {
+                let half_height = height / 2.0;
+                let half_width = half_height * aspect;
+                glm::ortho(
+                    -half_width,
+                    half_width,
+                    -half_height,
+                    half_height,
+                    near,
+                    far,
+                )
+            }
+            Projection::OffAxis {
+                left,
+                right,
+                bottom,
+                top,
+                near,
+                far,
+            } => {
+                #[rustfmt::skip]
+                let projection = glm::Mat4::new(
+                    2.0 * near / (right - left), 0.0, (right + left) / (right - left), 0.0,
+                    0.0, 2.0 * near / (top - bottom), (top + bottom) / (top - bottom), 0.0,
+                    0.0, 0.0, -(far + near) / (far - near), -2.0 * far * near / (far - near),
+                    0.0, 0.0, -1.0, 0.0,
+                );
+                projection
+            }
+        }
+    }
+
+    /// Vertical field of view, for the projections that have a fixed one.
+    pub fn fov(&self) -> Option<f32> {
+        match *self {
+            Projection::Perspective { fov, .. } | Projection::ReversedZInfinite { fov, .. } => {
+                Some(fov)
+            }
+            _ => None,
+        }
+    }
+
+    /// Narrows the field of view by `degrees`, kept between 1° and 75°. Orthographic
+    /// projections shrink their height by a matching amount, off-axis ones are unchanged.
+    pub fn zoom(&mut self, degrees: f32) {
+        match self {
+            Projection::Perspective { fov, .. } | Projection::ReversedZInfinite { fov, .. } => {
+                *fov = (*fov - degrees).clamp(1.0, 75.0);
+            }
+            Projection::Orthographic { height, .. } => {
+                *height = (*height * (-degrees / 45.0).exp()).max(1e-4);
+            }
+            Projection::OffAxis { .. } => {}
+        }
+    }
+
+    /// World units one pixel covers at `distance` in front of the camera.
+    pub fn units_per_pixel(&self, window_size: (u32, u32), distance: f32) -> f32 {
+        let visible_height = match *self {
+            Projection::Perspective { fov, .. } | Projection::ReversedZInfinite { fov, .. } => {
+                2.0 * distance * (fov.to_radians() / 2.0).tan()
+            }
+            Projection::Orthographic { height, .. } => height,
+            Projection::OffAxis {
+                bottom, top, near, ..
+            } => distance * (top - bottom) / near,
+        };
+        visible_height / window_size.1.max(1) as f32
+    }
+}
+
+/// Width over height, 1 when either is 0 such as while the window is minimised.
+pub fn aspect_ratio(window_size: (u32, u32)) -> f32 {
+    match window_size {
+        (0, _) | (_, 0) => 1.0,
+        (width, height) => width as f32 / height as f32,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Normalised device coordinates of a view space point.
+    fn project(projection: &glm::Mat4, point: glm::Vec3) -> glm::Vec3 {
+        let clip = projection * point.push(1.0);
+        clip.xyz() / clip.w
+    }
+
+    #[test]
+    fn reversed_z_maps_near_to_one_and_far_towards_zero() {
+        let near = 0.5;
+        let projection = Projection::ReversedZInfinite { fov: 60.0, near }.calculate((800, 600));
+
+        let depth = |distance: f32| project(&projection, glm::vec3(0.0, 0.0, -distance)).z;
+        assert!((depth(near) - 1.0).abs() < 1e-6);
+        assert!((depth(2.0 * near) - 0.5).abs() < 1e-6);
+        let mut previous = depth(near);
+        for distance in [1.0, 10.0, 1e3, 1e6] {
+            let depth = depth(distance);
+            assert!(depth > 0.0 && depth < previous, "{depth} at {distance}");
+            previous = depth;
+        }
+        assert!(previous < 1e-6);
+    }
+
+    #[test]
+    fn reversed_z_matches_the_perspective_field_of_view() {
+        let window_size = (800, 600);
+        let reversed = Projection::ReversedZInfinite {
+            fov: 60.0,
+            near: 0.1,
+        }
+        .calculate(window_size);
+        let perspective = Projection::perspective(60.0).calculate(window_size);
+        let point = glm::vec3(1.0, -2.0, -5.0);
+        let (a, b) = (project(&reversed, point), project(&perspective, point));
+        assert!((a.xy() - b.xy()).norm() < 1e-5);
+    }
+
+    #[test]
+    fn symmetric_off_axis_equals_perspective() {
+        let (fov, aspect, near, far) = (50f32, 1.5, 0.1, 100.0);
+        let top = near * (fov.to_radians() / 2.0).tan();
+        let right = top * aspect;
+        let off_axis = Projection::OffAxis {
+            left: -right,
+            right,
+            bottom: -top,
+            top,
+            near,
+            far,
+        }
+        .calculate((1, 1));
+        let perspective = glm::perspective(aspect, fov.to_radians(), near, far);
+        let difference = off_axis - perspective;
+        assert!(
+            difference.iter().all(|value| value.abs() < 1e-5),
+            "{difference}"
+        );
+    }
+
+    #[test]
+    fn off_axis_maps_its_edges_to_the_sides_of_clip_space() {
+        let projection = Projection::OffAxis {
+            left: -0.1,
+            right: 0.3,
+            bottom: -0.2,
+            top: 0.1,
+            near: 0.5,
+            far: 10.0,
+        }
+        .calculate((800, 600));
+        let bottom_left = project(&projection, glm::vec3(-0.1, -0.2, -0.5));
+        let top_right = project(&projection, glm::vec3(0.3, 0.1, -0.5));
+        assert!((bottom_left - glm::vec3(-1.0, -1.0, -1.0)).norm() < 1e-5);
+        assert!((top_right - glm::vec3(1.0, 1.0, -1.0)).norm() < 1e-5);
+        assert!((project(&projection, glm::vec3(0.0, 0.0, -10.0)).z - 1.0).abs() < 1e-5);
+    }
+}
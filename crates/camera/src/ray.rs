@@ -0,0 +1,276 @@
+use crate::Projection;
+use nalgebra_glm as glm;
+
+/// Half-line from `origin`, hit distances are in units of `direction`'s length.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Ray {
+    pub origin: glm::Vec3,
+    /// Unit length for rays made by [`screen_ray`].
+    pub direction: glm::Vec3,
+}
+
+impl Ray {
+    pub fn new(origin: &[f32; 3], direction: &[f32; 3]) -> Self {
+        Ray {
+            origin: glm::make_vec3(origin),
+            direction: glm::normalize(&glm::make_vec3(direction)),
+        }
+    }
+
+    pub fn at(&self, distance: f32) -> [f32; 3] {
+        (self.origin + distance * self.direction).into()
+    }
+
+    /// Into the space `matrix` maps to, e.g. the inverse model matrix to test a model's own
+    /// bounds. Distances stay comparable since the direction isn't renormalised.
+    pub fn transform(&self, matrix: &glm::Mat4) -> Self {
+        Ray {
+            origin: (matrix * self.origin.push(1.0)).xyz(),
+            direction: (matrix * self.direction.push(0.0)).xyz(),
+        }
+    }
+
+    /// Distance to where the ray enters the box, 0 when it starts inside.
+    pub fn intersect_aabb(&self, min: &[f32; 3], max: &[f32; 3]) -> Option<f32> {
+        let mut near = 0.0f32;
+        let mut far = f32::INFINITY;
+        for axis in 0..3 {
+            // Division by zero gives infinities, which the comparisons handle
+            let inverse = 1.0 / self.direction[axis];
+            let t0 = (min[axis] - self.origin[axis]) * inverse;
+            let t1 = (max[axis] - self.origin[axis]) * inverse;
+            near = near.max(t0.min(t1));
+            far = far.min(t0.max(t1));
+        }
+        (near <= far).then_some(near)
+    }
+
+    /// Distance to where the ray enters the sphere, 0 when it starts inside.
+    pub fn intersect_sphere(&self, center: &[f32; 3], radius: f32) -> Option<f32> {
+        let to_origin = self.origin - glm::make_vec3(center);
+        let a = glm::dot(&self.direction, &self.direction);
+        let b = glm::dot(&to_origin, &self.direction);
+        let c = glm::dot(&to_origin, &to_origin) - radius * radius;
+        let discriminant = b * b - a * c;
+        if discriminant < 0.0 {
+            return None;
+        }
+        let far = (-b + discriminant.sqrt()) / a;
+        if far < 0.0 {
+            return None;
+        }
+        Some(((-b - discriminant.sqrt()) / a).max(0.0))
+    }
+
+    /// Möller–Trumbore, both faces count as hits.
+    pub fn intersect_triangle(&self, a: &[f32; 3], b: &[f32; 3], c: &[f32; 3]) -> Option<f32> {
+        let a = glm::make_vec3(a);
+        let edge1 = glm::make_vec3(b) - a;
+        let edge2 = glm::make_vec3(c) - a;
+        let p = glm::cross(&self.direction, &edge2);
+        let determinant = glm::dot(&edge1, &p);
+        if determinant.abs() < f32::EPSILON {
+            return None;
+        }
+        let inverse = 1.0 / determinant;
+        let to_origin = self.origin - a;
+        let u = glm::dot(&to_origin, &p) * inverse;
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+        let q = glm::cross(&to_origin, &edge1);
+        let v = glm::dot(&self.direction, &q) * inverse;
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+        let distance = glm::dot(&edge2, &q) * inverse;
+        (distance >= 0.0).then_some(distance)
+    }
+
+    /// Closest hit against a triangle list, as the distance and the triangle's index. Without
+    /// `indices` every three positions form a triangle.
+    pub fn intersect_triangles(
+        &self,
+        positions: &[[f32; 3]],
+        indices: Option<&[u32]>,
+    ) -> Option<(f32, usize)> {
+        let triangle = |i: usize| match indices {
+            Some(indices) => [0, 1, 2].map(|corner| positions[indices[3 * i + corner] as usize]),
+            None => [0, 1, 2].map(|corner| positions[3 * i + corner]),
+        };
+        let count = indices.map_or(positions.len(), |indices| indices.len()) / 3;
+        (0..count)
+            .filter_map(|i| {
+                let [a, b, c] = triangle(i);
+                self.intersect_triangle(&a, &b, &c)
+                    .map(|distance| (distance, i))
+            })
+            .min_by(|(a, _), (b, _)| a.total_cmp(b))
+    }
+}
+
+/// World space ray through a cursor position with the origin at the top left, in the same
+/// units as `window_size`, i.e. pixels for the drawable size. Starts at the eye for
+/// perspective projections and on the near plane for orthographic ones.
+pub fn screen_ray(
+    view: &glm::Mat4,
+    projection: &Projection,
+    (x, y): (f32, f32),
+    window_size: (u32, u32),
+) -> Ray {
+    let ndc_x = 2.0 * x / window_size.0.max(1) as f32 - 1.0;
+    let ndc_y = 1.0 - 2.0 * y / window_size.1.max(1) as f32;
+    let aspect = crate::aspect_ratio(window_size);
+
+    let (origin, direction) = match *projection {
+        Projection::Perspective { fov, .. } | Projection::ReversedZInfinite { fov, .. } => {
+            let tan_half_fov = (fov.to_radians() / 2.0).tan();
+            (
+                glm::Vec3::zeros(),
+                glm::vec3(ndc_x * tan_half_fov * aspect, ndc_y * tan_half_fov, -1.0),
+            )
+        }
+        Projection::Orthographic { height, near, .. } => (
+            glm::vec3(ndc_x * height / 2.0 * aspect, ndc_y * height / 2.0, -near),
+            -glm::Vec3::z(),
+        ),
+        Projection::OffAxis {
+            left,
+            right,
+            bottom,
+            top,
+            near,
+            ..
+        } => (
+            glm::Vec3::zeros(),
+            glm::vec3(
+                glm::lerp_scalar(left, right, (ndc_x + 1.0) / 2.0),
+                glm::lerp_scalar(bottom, top, (ndc_y + 1.0) / 2.0),
+                -near,
+            ),
+        ),
+    };
+
+    let world_from_view = glm::inverse(view);
+    Ray {
+        origin: (world_from_view * origin.push(1.0)).xyz(),
+        direction: glm::normalize(&(world_from_view * direction.push(0.0)).xyz()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn approx(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-4
+    }
+
+    #[test]
+    fn boxes() {
+        let ray = Ray::new(&[0.0, 0.0, 5.0], &[0.0, 0.0, -1.0]);
+        assert!(approx(
+            ray.intersect_aabb(&[-1.0; 3], &[1.0; 3]).unwrap(),
+            4.0
+        ));
+        // Parallel to an axis and outside the slab on it
+        assert_eq!(
+            ray.intersect_aabb(&[2.0, -1.0, -1.0], &[3.0, 1.0, 1.0]),
+            None
+        );
+        // Behind the origin
+        assert_eq!(
+            ray.intersect_aabb(&[-1.0, -1.0, 6.0], &[1.0, 1.0, 7.0]),
+            None
+        );
+
+        let inside = Ray::new(&[0.0; 3], &[1.0, 1.0, 0.0]);
+        assert_eq!(inside.intersect_aabb(&[-1.0; 3], &[1.0; 3]), Some(0.0));
+    }
+
+    #[test]
+    fn spheres() {
+        let ray = Ray::new(&[0.0, 0.0, 5.0], &[0.0, 0.0, -1.0]);
+        assert!(approx(ray.intersect_sphere(&[0.0; 3], 1.0).unwrap(), 4.0));
+        assert_eq!(ray.intersect_sphere(&[3.0, 0.0, 0.0], 1.0), None);
+        assert_eq!(ray.intersect_sphere(&[0.0, 0.0, 10.0], 1.0), None);
+        assert_eq!(ray.intersect_sphere(&[0.0, 0.0, 5.0], 1.0), Some(0.0));
+    }
+
+    #[test]
+    fn triangles() {
+        let ray = Ray::new(&[0.25, 0.25, 1.0], &[0.0, 0.0, -1.0]);
+        let (a, b, c) = ([0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]);
+        assert!(approx(ray.intersect_triangle(&a, &b, &c).unwrap(), 1.0));
+        // Back faces count too
+        assert!(approx(ray.intersect_triangle(&a, &c, &b).unwrap(), 1.0));
+
+        let outside = Ray::new(&[1.0, 1.0, 1.0], &[0.0, 0.0, -1.0]);
+        assert_eq!(outside.intersect_triangle(&a, &b, &c), None);
+        let parallel = Ray::new(&[0.25, 0.25, 1.0], &[1.0, 0.0, 0.0]);
+        assert_eq!(parallel.intersect_triangle(&a, &b, &c), None);
+    }
+
+    #[test]
+    fn closest_triangle() {
+        let positions = [
+            [-1.0, -1.0, -2.0],
+            [1.0, -1.0, -2.0],
+            [0.0, 1.0, -2.0],
+            [-1.0, -1.0, -1.0],
+            [1.0, -1.0, -1.0],
+            [0.0, 1.0, -1.0],
+        ];
+        let ray = Ray::new(&[0.0; 3], &[0.0, 0.0, -1.0]);
+        let (distance, triangle) = ray.intersect_triangles(&positions, None).unwrap();
+        assert!(approx(distance, 1.0));
+        assert_eq!(triangle, 1);
+
+        let indices = [0, 1, 2];
+        let (distance, triangle) = ray.intersect_triangles(&positions, Some(&indices)).unwrap();
+        assert!(approx(distance, 2.0));
+        assert_eq!(triangle, 0);
+    }
+
+    #[test]
+    fn transform_keeps_distances_comparable() {
+        let ray = Ray::new(&[0.0, 0.0, 5.0], &[0.0, 0.0, -1.0]);
+        let model = glm::scaling(&glm::vec3(2.0, 2.0, 2.0));
+        let local = ray.transform(&glm::inverse(&model));
+        // The unit box scaled by 2 has its surface at z = 2, 3 units from the origin
+        let distance = local.intersect_aabb(&[-1.0; 3], &[1.0; 3]).unwrap();
+        assert!(approx(distance, 3.0));
+        assert!(approx(ray.at(distance)[2], 2.0));
+    }
+
+    #[test]
+    fn screen_ray_through_centre_follows_the_view() {
+        let view = glm::look_at(
+            &glm::vec3(0.0, 0.0, 5.0),
+            &glm::Vec3::zeros(),
+            &glm::Vec3::y(),
+        );
+        let ray = screen_ray(
+            &view,
+            &Projection::perspective(60.0),
+            (400.0, 300.0),
+            (800, 600),
+        );
+        assert!(approx(
+            glm::distance(&ray.origin, &glm::vec3(0.0, 0.0, 5.0)),
+            0.0
+        ));
+        assert!(approx(glm::distance(&ray.direction, &-glm::Vec3::z()), 0.0));
+
+        // The top left corner is up and to the left
+        let corner = screen_ray(
+            &view,
+            &Projection::perspective(60.0),
+            (0.0, 0.0),
+            (800, 600),
+        );
+        assert!(corner.direction.x < 0.0 && corner.direction.y > 0.0);
+        let expected_y = (30f32.to_radians()).tan();
+        assert!(approx(corner.direction.y / -corner.direction.z, expected_y));
+    }
+}
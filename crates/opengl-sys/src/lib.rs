@@ -19,6 +19,8 @@ pub enum Error {
     StackOutOfMemory,
     #[error("Invalid Frame Buffer Operation")]
     InvalidFrameBufferOperation,
+    #[error("{0} is not available in this context")]
+    Unsupported(&'static str),
 }
 
 pub fn get_error() -> Option<Error> {
@@ -51,6 +53,7 @@ pub enum DataType {
     U16,
     I32,
     U32,
+    U24U8,
     F32,
 }
 
@@ -59,7 +62,7 @@ impl DataType {
         match self {
             DataType::I8 | DataType::U8 => 1,
             DataType::I16 | DataType::U16 => 2,
-            DataType::I32 | DataType::U32 => 4,
+            DataType::I32 | DataType::U32 | DataType::U24U8 => 4,
             DataType::F32 => 4,
         }
     }
@@ -74,6 +77,7 @@ impl From<DataType> for GLenum {
             DataType::U16 => gl::UNSIGNED_SHORT,
             DataType::I32 => gl::INT,
             DataType::U32 => gl::UNSIGNED_INT,
+            DataType::U24U8 => gl::UNSIGNED_INT_24_8,
             DataType::F32 => gl::FLOAT,
         }
     }
@@ -287,6 +291,8 @@ pub fn link_program(program_id: ProgramID) -> Result<(), Error> {
 pub enum ProgramParameter {
     LinkStatus,
     InfoLogLength,
+    ActiveUniforms,
+    ActiveUniformMaxLength,
     // TODO: many more
 }
 
@@ -323,10 +329,110 @@ impl From<ProgramParameter> for GLenum {
         match value {
             ProgramParameter::LinkStatus => gl::LINK_STATUS,
             ProgramParameter::InfoLogLength => gl::INFO_LOG_LENGTH,
+            ProgramParameter::ActiveUniforms => gl::ACTIVE_UNIFORMS,
+            ProgramParameter::ActiveUniformMaxLength => gl::ACTIVE_UNIFORM_MAX_LENGTH,
         }
     }
 }
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum UniformType {
+    Sampler2D,
+    SamplerCube,
+    Sampler2DShadow,
+    Sampler2DMultisample,
+    IntSampler2D,
+    UnsignedIntSampler2D,
+    /// Any other sampler type, e.g. `sampler3D`, `sampler2DArray` or `usamplerBuffer`.
+    OtherSampler(GLenum),
+    Other(GLenum),
+}
+
+impl UniformType {
+    pub fn is_sampler(&self) -> bool {
+        !matches!(self, UniformType::Other(_))
+    }
+}
+
+impl From<GLenum> for UniformType {
+    fn from(value: GLenum) -> Self {
+        match value {
+            gl::SAMPLER_2D => UniformType::Sampler2D,
+            gl::SAMPLER_CUBE => UniformType::SamplerCube,
+            gl::SAMPLER_2D_SHADOW => UniformType::Sampler2DShadow,
+            gl::SAMPLER_2D_MULTISAMPLE => UniformType::Sampler2DMultisample,
+            gl::INT_SAMPLER_2D => UniformType::IntSampler2D,
+            gl::UNSIGNED_INT_SAMPLER_2D => UniformType::UnsignedIntSampler2D,
+            gl::SAMPLER_1D
+            | gl::SAMPLER_1D_SHADOW
+            | gl::SAMPLER_1D_ARRAY
+            | gl::SAMPLER_1D_ARRAY_SHADOW
+            | gl::SAMPLER_2D_ARRAY
+            | gl::SAMPLER_2D_ARRAY_SHADOW
+            | gl::SAMPLER_2D_MULTISAMPLE_ARRAY
+            | gl::SAMPLER_2D_RECT
+            | gl::SAMPLER_2D_RECT_SHADOW
+            | gl::SAMPLER_3D
+            | gl::SAMPLER_CUBE_SHADOW
+            | gl::SAMPLER_CUBE_MAP_ARRAY
+            | gl::SAMPLER_CUBE_MAP_ARRAY_SHADOW
+            | gl::SAMPLER_BUFFER
+            | gl::INT_SAMPLER_1D
+            | gl::INT_SAMPLER_1D_ARRAY
+            | gl::INT_SAMPLER_2D_ARRAY
+            | gl::INT_SAMPLER_2D_MULTISAMPLE
+            | gl::INT_SAMPLER_2D_MULTISAMPLE_ARRAY
+            | gl::INT_SAMPLER_2D_RECT
+            | gl::INT_SAMPLER_3D
+            | gl::INT_SAMPLER_CUBE
+            | gl::INT_SAMPLER_CUBE_MAP_ARRAY
+            | gl::INT_SAMPLER_BUFFER
+            | gl::UNSIGNED_INT_SAMPLER_1D
+            | gl::UNSIGNED_INT_SAMPLER_1D_ARRAY
+            | gl::UNSIGNED_INT_SAMPLER_2D_ARRAY
+            | gl::UNSIGNED_INT_SAMPLER_2D_MULTISAMPLE
+            | gl::UNSIGNED_INT_SAMPLER_2D_MULTISAMPLE_ARRAY
+            | gl::UNSIGNED_INT_SAMPLER_2D_RECT
+            | gl::UNSIGNED_INT_SAMPLER_3D
+            | gl::UNSIGNED_INT_SAMPLER_CUBE
+            | gl::UNSIGNED_INT_SAMPLER_CUBE_MAP_ARRAY
+            | gl::UNSIGNED_INT_SAMPLER_BUFFER => UniformType::OtherSampler(value),
+            _ => UniformType::Other(value),
+        }
+    }
+}
+
+/// Returns the name, array size and type of the active uniform at `index`.
+pub fn get_active_uniform(
+    program_id: ProgramID,
+    index: u32,
+    buffer_size: u32,
+) -> Result<(String, u32, UniformType), Error> {
+    let mut buffer = vec![0u8; buffer_size as usize];
+    let mut length = 0;
+    let mut size = 0;
+    let mut uniform_type = 0;
+    unsafe {
+        gl::GetActiveUniform(
+            program_id,
+            index,
+            buffer_size as GLsizei,
+            &mut length,
+            &mut size,
+            &mut uniform_type,
+            buffer.as_mut_ptr() as *mut GLchar,
+        )
+    };
+    buffer.truncate(length.max(0) as usize);
+    with_check_fn(|| {
+        (
+            String::from_utf8_lossy(&buffer).into_owned(),
+            size as u32,
+            uniform_type.into(),
+        )
+    })
+}
+
 pub type UniformLocation = GLuint;
 
 pub fn get_uniform_location(
@@ -352,6 +458,11 @@ pub fn set_uniform_i32(location: UniformLocation, data: i32) -> Result<(), Error
     assert_no_error()
 }
 
+pub fn set_uniform_u32(location: UniformLocation, data: u32) -> Result<(), Error> {
+    unsafe { gl::Uniform1ui(location as _, data) };
+    assert_no_error()
+}
+
 pub fn set_uniform_f32(location: UniformLocation, data: f32) -> Result<(), Error> {
     unsafe { gl::Uniform1f(location as _, data) };
     assert_no_error()
@@ -367,6 +478,20 @@ pub fn set_uniform_vec3(location: UniformLocation, data: &[f32; 3]) -> Result<()
     assert_no_error()
 }
 
+pub fn set_uniform_vec4(location: UniformLocation, data: &[f32; 4]) -> Result<(), Error> {
+    unsafe { gl::Uniform4fv(location as _, 1, data.as_ptr()) };
+    assert_no_error()
+}
+
+pub fn set_uniform_mat3(
+    location: UniformLocation,
+    transpose: bool,
+    data: &[f32],
+) -> Result<(), Error> {
+    unsafe { gl::UniformMatrix3fv(location as _, 1, bool_to_enum(transpose), data.as_ptr()) };
+    assert_no_error()
+}
+
 pub fn set_uniform_mat4(
     location: UniformLocation,
     transpose: bool,
@@ -376,6 +501,24 @@ pub fn set_uniform_mat4(
     assert_no_error()
 }
 
+/// Sets consecutive elements of a `mat4` array uniform, one per 16 floats of `data`.
+pub fn set_uniform_mat4_array(
+    location: UniformLocation,
+    transpose: bool,
+    data: &[f32],
+) -> Result<(), Error> {
+    let count = data.len() / 16;
+    unsafe {
+        gl::UniformMatrix4fv(
+            location as _,
+            count as _,
+            bool_to_enum(transpose),
+            data.as_ptr(),
+        )
+    };
+    assert_no_error()
+}
+
 pub type BufferID = GLuint;
 
 pub fn create_buffer() -> BufferID {
@@ -540,6 +683,7 @@ pub enum TextureCubeMapFaceTarget {
 #[derive(Copy, Clone, Debug)]
 pub enum TextureTarget {
     Image2D,
+    Image2DMultisample,
     CubeMap,
     CubeMapFace(TextureCubeMapFaceTarget),
 }
@@ -554,10 +698,16 @@ pub fn active_texture(texture_index: u32) -> Result<(), Error> {
     assert_no_error()
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum DepthFunc {
+    Never,
     Less,
+    Equal,
     LessEqual,
+    Greater,
+    NotEqual,
+    GreaterEqual,
+    Always,
 }
 
 pub fn set_depth_func(func: DepthFunc) {
@@ -567,8 +717,57 @@ pub fn set_depth_func(func: DepthFunc) {
 impl From<DepthFunc> for GLenum {
     fn from(value: DepthFunc) -> Self {
         match value {
+            DepthFunc::Never => gl::NEVER,
             DepthFunc::Less => gl::LESS,
+            DepthFunc::Equal => gl::EQUAL,
             DepthFunc::LessEqual => gl::LEQUAL,
+            DepthFunc::Greater => gl::GREATER,
+            DepthFunc::NotEqual => gl::NOTEQUAL,
+            DepthFunc::GreaterEqual => gl::GEQUAL,
+            DepthFunc::Always => gl::ALWAYS,
+        }
+    }
+}
+
+impl TryFrom<GLenum> for DepthFunc {
+    type Error = Error;
+    fn try_from(value: GLenum) -> Result<Self, Self::Error> {
+        match value {
+            gl::NEVER => Ok(DepthFunc::Never),
+            gl::LESS => Ok(DepthFunc::Less),
+            gl::EQUAL => Ok(DepthFunc::Equal),
+            gl::LEQUAL => Ok(DepthFunc::LessEqual),
+            gl::GREATER => Ok(DepthFunc::Greater),
+            gl::NOTEQUAL => Ok(DepthFunc::NotEqual),
+            gl::GEQUAL => Ok(DepthFunc::GreaterEqual),
+            gl::ALWAYS => Ok(DepthFunc::Always),
+            _ => Err(Error::Unknown(value)),
+        }
+    }
+}
+
+/// Depth range of clip space, GL defaults to [`ClipDepth::NegativeOneToOne`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ClipDepth {
+    NegativeOneToOne,
+    /// Needed for reversed-Z to keep its precision, requires GL 4.5 or ARB_clip_control.
+    ZeroToOne,
+}
+
+/// Fails with [`Error::Unsupported`] on contexts older than GL 4.5 without ARB_clip_control.
+pub fn set_clip_control(depth: ClipDepth) -> Result<(), Error> {
+    if !gl::ClipControl::is_loaded() {
+        return Err(Error::Unsupported("glClipControl"));
+    }
+    unsafe { gl::ClipControl(gl::LOWER_LEFT, depth.into()) };
+    assert_no_error()
+}
+
+impl From<ClipDepth> for GLenum {
+    fn from(value: ClipDepth) -> Self {
+        match value {
+            ClipDepth::NegativeOneToOne => gl::NEGATIVE_ONE_TO_ONE,
+            ClipDepth::ZeroToOne => gl::ZERO_TO_ONE,
         }
     }
 }
@@ -584,7 +783,12 @@ pub enum TextureParameterName {
 
 #[derive(Copy, Clone, Debug)]
 pub enum TextureParameterValue {
+    Nearest,
     Linear,
+    NearestMipmapNearest,
+    LinearMipmapNearest,
+    NearestMipmapLinear,
+    LinearMipmapLinear,
     ClampToEdge,
     Repeat,
     MirroredRepeat,
@@ -607,10 +811,39 @@ pub fn set_texture_parameter_value(
 
 #[derive(Copy, Clone, Debug)]
 pub enum TextureFormat {
+    Red,
     RGB,
     RGBA,
+    RGB8,
+    RGBA8,
+    SRGB8,
+    SRGB8Alpha8,
+    R16F,
+    RGBA16F,
+    R32UI,
+    /// Pixel data format for integer textures such as [`TextureFormat::R32UI`].
+    RedInteger,
+    DepthComponent,
+    DepthStencil,
 }
 
+/// Bytes of tightly packed pixel data, in the layout texture uploads and [`read_pixels`] use.
+pub fn pixel_data_size(
+    width: u32,
+    height: u32,
+    format: TextureFormat,
+    data_type: DataType,
+) -> usize {
+    let components = match format {
+        TextureFormat::RGB => 3,
+        TextureFormat::RGBA | TextureFormat::RGBA16F => 4,
+        _ => 1,
+    };
+    width as usize * height as usize * components * data_type.num_bytes() as usize
+}
+
+/// Rows of `data` are tightly packed. Fails with [`Error::InvalidValue`] if `data` is too small
+/// to fill the image.
 #[allow(clippy::too_many_arguments)]
 pub fn load_texture_image2d<Data>(
     texture_target: TextureTarget,
@@ -623,7 +856,14 @@ pub fn load_texture_image2d<Data>(
     data: Option<&[Data]>,
 ) -> Result<(), Error> {
     let internal_format: GLenum = internal_format.into();
+    if let Some(data) = data {
+        let required = pixel_data_size(width as _, height as _, data_format, data_type);
+        if std::mem::size_of_val(data) < required {
+            return Err(Error::InvalidValue);
+        }
+    }
     unsafe {
+        gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
         gl::TexImage2D(
             texture_target.into(),
             mipmap_level as _,
@@ -639,6 +879,63 @@ pub fn load_texture_image2d<Data>(
     assert_no_error()
 }
 
+/// Allocates storage for a multisampled texture, these can't be uploaded to or filtered.
+pub fn load_texture_image2d_multisample(
+    samples: u32,
+    internal_format: TextureFormat,
+    width: u64,
+    height: u64,
+    fixed_sample_locations: bool,
+) -> Result<(), Error> {
+    let internal_format: GLenum = internal_format.into();
+    unsafe {
+        gl::TexImage2DMultisample(
+            gl::TEXTURE_2D_MULTISAMPLE,
+            samples as _,
+            internal_format,
+            width as _,
+            height as _,
+            bool_to_enum(fixed_sample_locations),
+        )
+    };
+    assert_no_error()
+}
+
+/// Rows of `data` are tightly packed. Fails with [`Error::InvalidValue`] if `data` is too small
+/// to fill the region.
+#[allow(clippy::too_many_arguments)]
+pub fn load_texture_sub_image2d<Data>(
+    texture_target: TextureTarget,
+    mipmap_level: u32,
+    x_offset: u64,
+    y_offset: u64,
+    width: u64,
+    height: u64,
+    data_format: TextureFormat,
+    data_type: DataType,
+    data: &[Data],
+) -> Result<(), Error> {
+    let required = pixel_data_size(width as _, height as _, data_format, data_type);
+    if std::mem::size_of_val(data) < required {
+        return Err(Error::InvalidValue);
+    }
+    unsafe {
+        gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
+        gl::TexSubImage2D(
+            texture_target.into(),
+            mipmap_level as _,
+            x_offset as _,
+            y_offset as _,
+            width as _,
+            height as _,
+            data_format.into(),
+            data_type.into(),
+            data.as_ptr() as _,
+        )
+    };
+    assert_no_error()
+}
+
 pub fn generate_mipmaps(texture_target: TextureTarget) -> Result<(), Error> {
     unsafe { gl::GenerateMipmap(texture_target.into()) };
     assert_no_error()
@@ -648,6 +945,7 @@ impl From<TextureTarget> for GLuint {
     fn from(value: TextureTarget) -> Self {
         match value {
             TextureTarget::Image2D => gl::TEXTURE_2D,
+            TextureTarget::Image2DMultisample => gl::TEXTURE_2D_MULTISAMPLE,
             TextureTarget::CubeMap => gl::TEXTURE_CUBE_MAP,
             TextureTarget::CubeMapFace(face_target) => face_target.into(),
         }
@@ -682,7 +980,12 @@ impl From<TextureParameterName> for GLenum {
 impl From<TextureParameterValue> for GLint {
     fn from(value: TextureParameterValue) -> Self {
         (match value {
+            TextureParameterValue::Nearest => gl::NEAREST,
             TextureParameterValue::Linear => gl::LINEAR,
+            TextureParameterValue::NearestMipmapNearest => gl::NEAREST_MIPMAP_NEAREST,
+            TextureParameterValue::LinearMipmapNearest => gl::LINEAR_MIPMAP_NEAREST,
+            TextureParameterValue::NearestMipmapLinear => gl::NEAREST_MIPMAP_LINEAR,
+            TextureParameterValue::LinearMipmapLinear => gl::LINEAR_MIPMAP_LINEAR,
             TextureParameterValue::ClampToEdge => gl::CLAMP_TO_EDGE,
             TextureParameterValue::Repeat => gl::REPEAT,
             TextureParameterValue::MirroredRepeat => gl::MIRRORED_REPEAT,
@@ -693,12 +996,56 @@ impl From<TextureParameterValue> for GLint {
 impl From<TextureFormat> for GLenum {
     fn from(value: TextureFormat) -> Self {
         match value {
+            TextureFormat::Red => gl::RED,
             TextureFormat::RGB => gl::RGB,
             TextureFormat::RGBA => gl::RGBA,
+            TextureFormat::RGB8 => gl::RGB8,
+            TextureFormat::RGBA8 => gl::RGBA8,
+            TextureFormat::SRGB8 => gl::SRGB8,
+            TextureFormat::SRGB8Alpha8 => gl::SRGB8_ALPHA8,
+            TextureFormat::R16F => gl::R16F,
+            TextureFormat::RGBA16F => gl::RGBA16F,
+            TextureFormat::R32UI => gl::R32UI,
+            TextureFormat::RedInteger => gl::RED_INTEGER,
+            TextureFormat::DepthComponent => gl::DEPTH_COMPONENT,
+            TextureFormat::DepthStencil => gl::DEPTH_STENCIL,
         }
     }
 }
 
+pub type SamplerID = GLuint;
+
+pub fn create_sampler() -> SamplerID {
+    let mut id = 0;
+    unsafe { gl::GenSamplers(1, &mut id) };
+    id
+}
+
+pub fn delete_sampler(sampler_id: SamplerID) -> Result<(), Error> {
+    unsafe { gl::DeleteSamplers(1, &sampler_id) };
+    assert_no_error()
+}
+
+pub fn bind_sampler(texture_index: u32, sampler_id: SamplerID) -> Result<(), Error> {
+    unsafe { gl::BindSampler(texture_index, sampler_id) };
+    assert_no_error()
+}
+
+pub fn set_sampler_parameter_value(
+    sampler_id: SamplerID,
+    texture_parameter_name: TextureParameterName,
+    texture_parameter_value: TextureParameterValue,
+) -> Result<(), Error> {
+    unsafe {
+        gl::SamplerParameteri(
+            sampler_id,
+            texture_parameter_name.into(),
+            texture_parameter_value.into(),
+        )
+    };
+    assert_no_error()
+}
+
 pub type FrameBufferID = GLuint;
 
 pub fn create_frame_buffer() -> FrameBufferID {
@@ -709,19 +1056,44 @@ pub fn create_frame_buffer() -> FrameBufferID {
     id
 }
 
+#[derive(Copy, Clone, Debug)]
 pub enum FrameBufferTarget {
     All,
     Read,
     Draw,
 }
 
+pub fn delete_frame_buffer(id: FrameBufferID) -> Result<(), Error> {
+    unsafe { gl::DeleteFramebuffers(1, &id) };
+    assert_no_error()
+}
+
 pub fn bind_frame_buffer(id: FrameBufferID, target: FrameBufferTarget) -> Result<(), Error> {
     unsafe { gl::BindFramebuffer(target.into(), id) };
     assert_no_error()
 }
 
+/// Returns `None` when complete, otherwise the raw status describing what is missing.
+pub fn check_frame_buffer_status(target: FrameBufferTarget) -> Result<Option<GLenum>, Error> {
+    let status = unsafe { gl::CheckFramebufferStatus(target.into()) };
+    assert_no_error()?;
+    Ok((status != gl::FRAMEBUFFER_COMPLETE).then_some(status))
+}
+
+/// Routes fragment shader output `i` to colour attachment `colour_attachments[i]`.
+pub fn set_draw_buffers(colour_attachments: &[u32]) -> Result<(), Error> {
+    let buffers: Vec<GLenum> = colour_attachments
+        .iter()
+        .map(|index| gl::COLOR_ATTACHMENT0 + index)
+        .collect();
+    unsafe { gl::DrawBuffers(buffers.len() as _, buffers.as_ptr()) };
+    assert_no_error()
+}
+
+#[derive(Copy, Clone, Debug)]
 pub enum FrameBufferAttachment {
     Colour(u32),
+    Depth,
     DepthStencil,
 }
 
@@ -758,6 +1130,7 @@ impl From<FrameBufferAttachment> for GLenum {
     fn from(value: FrameBufferAttachment) -> Self {
         match value {
             FrameBufferAttachment::Colour(index) => gl::COLOR_ATTACHMENT0 + index,
+            FrameBufferAttachment::Depth => gl::DEPTH_ATTACHMENT,
             FrameBufferAttachment::DepthStencil => gl::DEPTH_STENCIL_ATTACHMENT,
         }
     }
@@ -771,12 +1144,20 @@ pub fn create_render_buffer() -> RenderBufferID {
     id
 }
 
+pub fn delete_render_buffer(id: RenderBufferID) -> Result<(), Error> {
+    unsafe { gl::DeleteRenderbuffers(1, &id) };
+    assert_no_error()
+}
+
 pub fn bind_render_buffer(id: RenderBufferID) -> Result<(), Error> {
     unsafe { gl::BindRenderbuffer(gl::RENDERBUFFER, id) };
     assert_no_error()
 }
 
+#[derive(Copy, Clone, Debug)]
 pub enum RenderBufferStorageFormat {
+    RGBA8,
+    RGBA16F,
     Depth24Stencil8,
 }
 
@@ -796,9 +1177,29 @@ pub fn render_buffer_storage(
     assert_no_error()
 }
 
+pub fn render_buffer_storage_multisample(
+    samples: u32,
+    internal_format: RenderBufferStorageFormat,
+    width: u32,
+    height: u32,
+) -> Result<(), Error> {
+    unsafe {
+        gl::RenderbufferStorageMultisample(
+            gl::RENDERBUFFER,
+            samples as _,
+            internal_format.into(),
+            width as _,
+            height as _,
+        )
+    };
+    assert_no_error()
+}
+
 impl From<RenderBufferStorageFormat> for GLenum {
     fn from(value: RenderBufferStorageFormat) -> Self {
         match value {
+            RenderBufferStorageFormat::RGBA8 => gl::RGBA8,
+            RenderBufferStorageFormat::RGBA16F => gl::RGBA16F,
             RenderBufferStorageFormat::Depth24Stencil8 => gl::DEPTH24_STENCIL8,
         }
     }
@@ -820,8 +1221,166 @@ pub fn frame_buffer_render_buffer(
     assert_no_error()
 }
 
+#[derive(Copy, Clone, Debug)]
+pub enum BlitFilter {
+    Nearest,
+    Linear,
+}
+
+/// Copies a rectangle from the bound read frame buffer to the bound draw frame buffer,
+/// resolving multisampled sources. Rectangles are `(x0, y0, x1, y1)`.
+pub fn blit_frame_buffer(
+    source: (i32, i32, i32, i32),
+    destination: (i32, i32, i32, i32),
+    buffers: &[BufferBit],
+    filter: BlitFilter,
+) -> Result<(), Error> {
+    let mask = buffers
+        .iter()
+        .fold(0, |mask, buffer| mask | GLenum::from(*buffer));
+    unsafe {
+        gl::BlitFramebuffer(
+            source.0,
+            source.1,
+            source.2,
+            source.3,
+            destination.0,
+            destination.1,
+            destination.2,
+            destination.3,
+            mask,
+            filter.into(),
+        )
+    };
+    assert_no_error()
+}
+
+impl From<BlitFilter> for GLenum {
+    fn from(value: BlitFilter) -> Self {
+        match value {
+            BlitFilter::Nearest => gl::NEAREST,
+            BlitFilter::Linear => gl::LINEAR,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub enum IntegerParameter {
+    MaxCombinedTextureImageUnits,
+    MaxSamples,
+    DepthFunc,
+    BlendSourceRGB,
+    BlendDestinationRGB,
+    BlendSourceAlpha,
+    BlendDestinationAlpha,
+    BlendEquationRGB,
+    CullFaceMode,
+    FrontFace,
+    PolygonMode,
+    ScissorBox,
+    StencilFunc,
+    StencilReference,
+    StencilValueMask,
+    StencilWriteMask,
+    StencilFail,
+    StencilPassDepthFail,
+    StencilPassDepthPass,
+}
+
+pub fn get_integer(parameter: IntegerParameter) -> Result<i32, Error> {
+    let [res] = get_integers(parameter)?;
+    Ok(res)
+}
+
+/// For parameters that hold several values, e.g. [`IntegerParameter::ScissorBox`]. No parameter
+/// holds more than 4, so larger `N` fails to compile.
+pub fn get_integers<const N: usize>(parameter: IntegerParameter) -> Result<[i32; N], Error> {
+    const { assert!(N <= 4, "integer parameters hold at most 4 values") };
+    // Some queries write more values than asked for (PolygonMode returns front and back)
+    let mut res = [0; 4];
+    unsafe { gl::GetIntegerv(parameter.into(), res.as_mut_ptr()) };
+    let mut values = [0; N];
+    values.copy_from_slice(&res[..N]);
+    with_check(values)
+}
+
+impl From<IntegerParameter> for GLenum {
+    fn from(value: IntegerParameter) -> Self {
+        match value {
+            IntegerParameter::MaxCombinedTextureImageUnits => gl::MAX_COMBINED_TEXTURE_IMAGE_UNITS,
+            IntegerParameter::MaxSamples => gl::MAX_SAMPLES,
+            IntegerParameter::DepthFunc => gl::DEPTH_FUNC,
+            IntegerParameter::BlendSourceRGB => gl::BLEND_SRC_RGB,
+            IntegerParameter::BlendDestinationRGB => gl::BLEND_DST_RGB,
+            IntegerParameter::BlendSourceAlpha => gl::BLEND_SRC_ALPHA,
+            IntegerParameter::BlendDestinationAlpha => gl::BLEND_DST_ALPHA,
+            IntegerParameter::BlendEquationRGB => gl::BLEND_EQUATION_RGB,
+            IntegerParameter::CullFaceMode => gl::CULL_FACE_MODE,
+            IntegerParameter::FrontFace => gl::FRONT_FACE,
+            IntegerParameter::PolygonMode => gl::POLYGON_MODE,
+            IntegerParameter::ScissorBox => gl::SCISSOR_BOX,
+            IntegerParameter::StencilFunc => gl::STENCIL_FUNC,
+            IntegerParameter::StencilReference => gl::STENCIL_REF,
+            IntegerParameter::StencilValueMask => gl::STENCIL_VALUE_MASK,
+            IntegerParameter::StencilWriteMask => gl::STENCIL_WRITEMASK,
+            IntegerParameter::StencilFail => gl::STENCIL_FAIL,
+            IntegerParameter::StencilPassDepthFail => gl::STENCIL_PASS_DEPTH_FAIL,
+            IntegerParameter::StencilPassDepthPass => gl::STENCIL_PASS_DEPTH_PASS,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub enum BooleanParameter {
+    DepthWriteMask,
+}
+
+pub fn get_boolean(parameter: BooleanParameter) -> Result<bool, Error> {
+    let mut res = gl::FALSE;
+    unsafe { gl::GetBooleanv(parameter.into(), &mut res) };
+    with_check(res == gl::TRUE)
+}
+
+impl From<BooleanParameter> for GLenum {
+    fn from(value: BooleanParameter) -> Self {
+        match value {
+            BooleanParameter::DepthWriteMask => gl::DEPTH_WRITEMASK,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub enum FloatParameter {
+    LineWidth,
+}
+
+pub fn get_float(parameter: FloatParameter) -> Result<f32, Error> {
+    let mut res = 0.0;
+    unsafe { gl::GetFloatv(parameter.into(), &mut res) };
+    with_check(res)
+}
+
+impl From<FloatParameter> for GLenum {
+    fn from(value: FloatParameter) -> Self {
+        match value {
+            FloatParameter::LineWidth => gl::LINE_WIDTH,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
 pub enum Feature {
     DepthTest,
+    Blend,
+    CullFace,
+    StencilTest,
+    ScissorTest,
+    Multisample,
+}
+
+pub fn is_enabled(feature: Feature) -> Result<bool, Error> {
+    let enabled = unsafe { gl::IsEnabled(feature.into()) };
+    with_check(enabled == gl::TRUE)
 }
 
 pub fn enable(feature: Feature) -> Result<(), Error> {
@@ -838,13 +1397,325 @@ impl From<Feature> for GLenum {
     fn from(value: Feature) -> Self {
         match value {
             Feature::DepthTest => gl::DEPTH_TEST,
+            Feature::Blend => gl::BLEND,
+            Feature::CullFace => gl::CULL_FACE,
+            Feature::StencilTest => gl::STENCIL_TEST,
+            Feature::ScissorTest => gl::SCISSOR_TEST,
+            Feature::Multisample => gl::MULTISAMPLE,
         }
     }
 }
 
+pub fn set_depth_mask(write_enabled: bool) -> Result<(), Error> {
+    unsafe { gl::DepthMask(bool_to_enum(write_enabled)) };
+    assert_no_error()
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BlendFactor {
+    Zero,
+    One,
+    SourceColour,
+    OneMinusSourceColour,
+    DestinationColour,
+    OneMinusDestinationColour,
+    SourceAlpha,
+    OneMinusSourceAlpha,
+    DestinationAlpha,
+    OneMinusDestinationAlpha,
+    ConstantColour,
+    OneMinusConstantColour,
+    ConstantAlpha,
+    OneMinusConstantAlpha,
+}
+
+pub fn set_blend_func(source: BlendFactor, destination: BlendFactor) -> Result<(), Error> {
+    unsafe { gl::BlendFunc(source.into(), destination.into()) };
+    assert_no_error()
+}
+
+pub fn set_blend_func_separate(
+    source_rgb: BlendFactor,
+    destination_rgb: BlendFactor,
+    source_alpha: BlendFactor,
+    destination_alpha: BlendFactor,
+) -> Result<(), Error> {
+    unsafe {
+        gl::BlendFuncSeparate(
+            source_rgb.into(),
+            destination_rgb.into(),
+            source_alpha.into(),
+            destination_alpha.into(),
+        )
+    };
+    assert_no_error()
+}
+
+impl From<BlendFactor> for GLenum {
+    fn from(value: BlendFactor) -> Self {
+        match value {
+            BlendFactor::Zero => gl::ZERO,
+            BlendFactor::One => gl::ONE,
+            BlendFactor::SourceColour => gl::SRC_COLOR,
+            BlendFactor::OneMinusSourceColour => gl::ONE_MINUS_SRC_COLOR,
+            BlendFactor::DestinationColour => gl::DST_COLOR,
+            BlendFactor::OneMinusDestinationColour => gl::ONE_MINUS_DST_COLOR,
+            BlendFactor::SourceAlpha => gl::SRC_ALPHA,
+            BlendFactor::OneMinusSourceAlpha => gl::ONE_MINUS_SRC_ALPHA,
+            BlendFactor::DestinationAlpha => gl::DST_ALPHA,
+            BlendFactor::OneMinusDestinationAlpha => gl::ONE_MINUS_DST_ALPHA,
+            BlendFactor::ConstantColour => gl::CONSTANT_COLOR,
+            BlendFactor::OneMinusConstantColour => gl::ONE_MINUS_CONSTANT_COLOR,
+            BlendFactor::ConstantAlpha => gl::CONSTANT_ALPHA,
+            BlendFactor::OneMinusConstantAlpha => gl::ONE_MINUS_CONSTANT_ALPHA,
+        }
+    }
+}
+
+impl TryFrom<GLenum> for BlendFactor {
+    type Error = Error;
+    fn try_from(value: GLenum) -> Result<Self, Self::Error> {
+        match value {
+            gl::ZERO => Ok(BlendFactor::Zero),
+            gl::ONE => Ok(BlendFactor::One),
+            gl::SRC_COLOR => Ok(BlendFactor::SourceColour),
+            gl::ONE_MINUS_SRC_COLOR => Ok(BlendFactor::OneMinusSourceColour),
+            gl::DST_COLOR => Ok(BlendFactor::DestinationColour),
+            gl::ONE_MINUS_DST_COLOR => Ok(BlendFactor::OneMinusDestinationColour),
+            gl::SRC_ALPHA => Ok(BlendFactor::SourceAlpha),
+            gl::ONE_MINUS_SRC_ALPHA => Ok(BlendFactor::OneMinusSourceAlpha),
+            gl::DST_ALPHA => Ok(BlendFactor::DestinationAlpha),
+            gl::ONE_MINUS_DST_ALPHA => Ok(BlendFactor::OneMinusDestinationAlpha),
+            gl::CONSTANT_COLOR => Ok(BlendFactor::ConstantColour),
+            gl::ONE_MINUS_CONSTANT_COLOR => Ok(BlendFactor::OneMinusConstantColour),
+            gl::CONSTANT_ALPHA => Ok(BlendFactor::ConstantAlpha),
+            gl::ONE_MINUS_CONSTANT_ALPHA => Ok(BlendFactor::OneMinusConstantAlpha),
+            _ => Err(Error::Unknown(value)),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BlendEquation {
+    Add,
+    Subtract,
+    ReverseSubtract,
+    Min,
+    Max,
+}
+
+pub fn set_blend_equation(equation: BlendEquation) -> Result<(), Error> {
+    unsafe { gl::BlendEquation(equation.into()) };
+    assert_no_error()
+}
+
+impl From<BlendEquation> for GLenum {
+    fn from(value: BlendEquation) -> Self {
+        match value {
+            BlendEquation::Add => gl::FUNC_ADD,
+            BlendEquation::Subtract => gl::FUNC_SUBTRACT,
+            BlendEquation::ReverseSubtract => gl::FUNC_REVERSE_SUBTRACT,
+            BlendEquation::Min => gl::MIN,
+            BlendEquation::Max => gl::MAX,
+        }
+    }
+}
+
+impl TryFrom<GLenum> for BlendEquation {
+    type Error = Error;
+    fn try_from(value: GLenum) -> Result<Self, Self::Error> {
+        match value {
+            gl::FUNC_ADD => Ok(BlendEquation::Add),
+            gl::FUNC_SUBTRACT => Ok(BlendEquation::Subtract),
+            gl::FUNC_REVERSE_SUBTRACT => Ok(BlendEquation::ReverseSubtract),
+            gl::MIN => Ok(BlendEquation::Min),
+            gl::MAX => Ok(BlendEquation::Max),
+            _ => Err(Error::Unknown(value)),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CullFaceMode {
+    Front,
+    Back,
+    FrontAndBack,
+}
+
+pub fn set_cull_face(mode: CullFaceMode) -> Result<(), Error> {
+    unsafe { gl::CullFace(mode.into()) };
+    assert_no_error()
+}
+
+impl From<CullFaceMode> for GLenum {
+    fn from(value: CullFaceMode) -> Self {
+        match value {
+            CullFaceMode::Front => gl::FRONT,
+            CullFaceMode::Back => gl::BACK,
+            CullFaceMode::FrontAndBack => gl::FRONT_AND_BACK,
+        }
+    }
+}
+
+impl TryFrom<GLenum> for CullFaceMode {
+    type Error = Error;
+    fn try_from(value: GLenum) -> Result<Self, Self::Error> {
+        match value {
+            gl::FRONT => Ok(CullFaceMode::Front),
+            gl::BACK => Ok(CullFaceMode::Back),
+            gl::FRONT_AND_BACK => Ok(CullFaceMode::FrontAndBack),
+            _ => Err(Error::Unknown(value)),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FrontFace {
+    Clockwise,
+    CounterClockwise,
+}
+
+pub fn set_front_face(front_face: FrontFace) -> Result<(), Error> {
+    unsafe { gl::FrontFace(front_face.into()) };
+    assert_no_error()
+}
+
+impl From<FrontFace> for GLenum {
+    fn from(value: FrontFace) -> Self {
+        match value {
+            FrontFace::Clockwise => gl::CW,
+            FrontFace::CounterClockwise => gl::CCW,
+        }
+    }
+}
+
+impl TryFrom<GLenum> for FrontFace {
+    type Error = Error;
+    fn try_from(value: GLenum) -> Result<Self, Self::Error> {
+        match value {
+            gl::CW => Ok(FrontFace::Clockwise),
+            gl::CCW => Ok(FrontFace::CounterClockwise),
+            _ => Err(Error::Unknown(value)),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PolygonMode {
+    Point,
+    Line,
+    Fill,
+}
+
+/// Core profile only supports setting front and back faces together.
+pub fn set_polygon_mode(mode: PolygonMode) -> Result<(), Error> {
+    unsafe { gl::PolygonMode(gl::FRONT_AND_BACK, mode.into()) };
+    assert_no_error()
+}
+
+impl From<PolygonMode> for GLenum {
+    fn from(value: PolygonMode) -> Self {
+        match value {
+            PolygonMode::Point => gl::POINT,
+            PolygonMode::Line => gl::LINE,
+            PolygonMode::Fill => gl::FILL,
+        }
+    }
+}
+
+impl TryFrom<GLenum> for PolygonMode {
+    type Error = Error;
+    fn try_from(value: GLenum) -> Result<Self, Self::Error> {
+        match value {
+            gl::POINT => Ok(PolygonMode::Point),
+            gl::LINE => Ok(PolygonMode::Line),
+            gl::FILL => Ok(PolygonMode::Fill),
+            _ => Err(Error::Unknown(value)),
+        }
+    }
+}
+
+/// Stencil comparisons share the depth comparison functions.
+pub type StencilFunc = DepthFunc;
+
+pub fn set_stencil_func(func: StencilFunc, reference: i32, mask: u32) -> Result<(), Error> {
+    unsafe { gl::StencilFunc(func.into(), reference, mask) };
+    assert_no_error()
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum StencilOp {
+    Keep,
+    Zero,
+    Replace,
+    Increment,
+    IncrementWrap,
+    Decrement,
+    DecrementWrap,
+    Invert,
+}
+
+pub fn set_stencil_op(
+    stencil_fail: StencilOp,
+    depth_fail: StencilOp,
+    pass: StencilOp,
+) -> Result<(), Error> {
+    unsafe { gl::StencilOp(stencil_fail.into(), depth_fail.into(), pass.into()) };
+    assert_no_error()
+}
+
+pub fn set_stencil_mask(mask: u32) -> Result<(), Error> {
+    unsafe { gl::StencilMask(mask) };
+    assert_no_error()
+}
+
+impl From<StencilOp> for GLenum {
+    fn from(value: StencilOp) -> Self {
+        match value {
+            StencilOp::Keep => gl::KEEP,
+            StencilOp::Zero => gl::ZERO,
+            StencilOp::Replace => gl::REPLACE,
+            StencilOp::Increment => gl::INCR,
+            StencilOp::IncrementWrap => gl::INCR_WRAP,
+            StencilOp::Decrement => gl::DECR,
+            StencilOp::DecrementWrap => gl::DECR_WRAP,
+            StencilOp::Invert => gl::INVERT,
+        }
+    }
+}
+
+impl TryFrom<GLenum> for StencilOp {
+    type Error = Error;
+    fn try_from(value: GLenum) -> Result<Self, Self::Error> {
+        match value {
+            gl::KEEP => Ok(StencilOp::Keep),
+            gl::ZERO => Ok(StencilOp::Zero),
+            gl::REPLACE => Ok(StencilOp::Replace),
+            gl::INCR => Ok(StencilOp::Increment),
+            gl::INCR_WRAP => Ok(StencilOp::IncrementWrap),
+            gl::DECR => Ok(StencilOp::Decrement),
+            gl::DECR_WRAP => Ok(StencilOp::DecrementWrap),
+            gl::INVERT => Ok(StencilOp::Invert),
+            _ => Err(Error::Unknown(value)),
+        }
+    }
+}
+
+pub fn set_line_width(width: f32) -> Result<(), Error> {
+    unsafe { gl::LineWidth(width) };
+    assert_no_error()
+}
+
+pub fn set_scissor(x: i32, y: i32, width: u32, height: u32) -> Result<(), Error> {
+    unsafe { gl::Scissor(x, y, width as _, height as _) };
+    assert_no_error()
+}
+
+#[derive(Copy, Clone, Debug)]
 pub enum BufferBit {
     Colour,
     Depth,
+    Stencil,
 }
 
 pub fn clear(buffer: BufferBit) -> Result<(), Error> {
@@ -857,11 +1728,57 @@ pub fn clear_colour(r: f32, g: f32, b: f32, a: f32) -> Result<(), Error> {
     assert_no_error()
 }
 
+pub fn clear_depth(depth: f32) -> Result<(), Error> {
+    unsafe { gl::ClearDepthf(depth) };
+    assert_no_error()
+}
+
+/// Clears a single draw buffer of the bound frame buffer, leaving the others untouched.
+pub fn clear_draw_buffer_colour(draw_buffer: u32, colour: &[f32; 4]) -> Result<(), Error> {
+    unsafe { gl::ClearBufferfv(gl::COLOR, draw_buffer as _, colour.as_ptr()) };
+    assert_no_error()
+}
+
+pub fn clear_draw_buffer_uint(draw_buffer: u32, value: &[u32; 4]) -> Result<(), Error> {
+    unsafe { gl::ClearBufferuiv(gl::COLOR, draw_buffer as _, value.as_ptr()) };
+    assert_no_error()
+}
+
+/// Reads a rectangle of the bound read frame buffer, with the origin at the bottom left. Fails
+/// with [`Error::InvalidValue`] if `data` is too small to hold it.
+pub fn read_pixels<Data>(
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+    format: TextureFormat,
+    data_type: DataType,
+    data: &mut [Data],
+) -> Result<(), Error> {
+    if std::mem::size_of_val(data) < pixel_data_size(width, height, format, data_type) {
+        return Err(Error::InvalidValue);
+    }
+    unsafe {
+        gl::PixelStorei(gl::PACK_ALIGNMENT, 1);
+        gl::ReadPixels(
+            x as _,
+            y as _,
+            width as _,
+            height as _,
+            format.into(),
+            data_type.into(),
+            data.as_mut_ptr() as _,
+        )
+    };
+    assert_no_error()
+}
+
 impl From<BufferBit> for GLenum {
     fn from(value: BufferBit) -> Self {
         match value {
             BufferBit::Colour => gl::COLOR_BUFFER_BIT,
             BufferBit::Depth => gl::DEPTH_BUFFER_BIT,
+            BufferBit::Stencil => gl::STENCIL_BUFFER_BIT,
         }
     }
 }
@@ -889,3 +1806,40 @@ where
         None => Ok(f()),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pixel_data_rows_are_tightly_packed() {
+        // Padding each row to 4 bytes would need 36
+        assert_eq!(pixel_data_size(3, 3, TextureFormat::RGB, DataType::U8), 27);
+        assert_eq!(pixel_data_size(3, 1, TextureFormat::RGBA, DataType::U8), 12);
+        assert_eq!(
+            pixel_data_size(2, 2, TextureFormat::RGBA16F, DataType::F32),
+            64
+        );
+        assert_eq!(pixel_data_size(5, 1, TextureFormat::Red, DataType::U8), 5);
+    }
+
+    #[test]
+    fn every_sampler_type_is_a_sampler() {
+        for sampler in [
+            gl::SAMPLER_2D,
+            gl::SAMPLER_3D,
+            gl::SAMPLER_1D,
+            gl::SAMPLER_2D_ARRAY,
+            gl::SAMPLER_CUBE_SHADOW,
+            gl::SAMPLER_2D_ARRAY_SHADOW,
+            gl::INT_SAMPLER_CUBE,
+            gl::UNSIGNED_INT_SAMPLER_3D,
+            gl::SAMPLER_BUFFER,
+        ] {
+            assert!(UniformType::from(sampler).is_sampler(), "{sampler:#x}");
+        }
+        for other in [gl::FLOAT_MAT4, gl::FLOAT_VEC3, gl::INT, gl::BOOL] {
+            assert!(!UniformType::from(other).is_sampler(), "{other:#x}");
+        }
+    }
+}
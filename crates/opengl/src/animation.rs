@@ -0,0 +1,413 @@
+use crate::scene_graph::*;
+use nalgebra_glm as glm;
+use std::collections::{HashMap, HashSet};
+
+/// Size of the `bones` array skinning vertex shaders are expected to declare.
+pub const MAX_BONES: usize = 100;
+/// Bones that can influence a single vertex.
+pub const MAX_BONE_INFLUENCES: usize = 4;
+
+pub struct Bone {
+    pub name: String,
+    /// `None` for joints outside the scene, which stay at the identity.
+    pub node: Option<NodeId>,
+    /// From mesh space to the bone's space in the bind pose.
+    pub offset: glm::Mat4,
+}
+
+#[derive(Default)]
+pub struct Skeleton {
+    pub bones: Vec<Bone>,
+}
+
+impl Skeleton {
+    pub fn find(&self, name: &str) -> Option<usize> {
+        self.bones.iter().position(|bone| bone.name == name)
+    }
+
+    /// One matrix per bone taking mesh space vertices to their posed position in model space.
+    pub fn palette(&self, graph: &SceneGraph) -> Vec<glm::Mat4> {
+        self.bones
+            .iter()
+            .map(|bone| {
+                bone.node
+                    .and_then(|node| graph.node(node))
+                    .map_or_else(glm::identity, |node| node.world_transform() * bone.offset)
+            })
+            .collect()
+    }
+}
+
+/// A node's local transform split into parts that can be interpolated.
+#[derive(Copy, Clone, Debug)]
+pub struct Pose {
+    pub translation: glm::Vec3,
+    pub rotation: glm::Quat,
+    pub scale: glm::Vec3,
+}
+
+impl Default for Pose {
+    fn default() -> Self {
+        Pose {
+            translation: glm::vec3(0.0, 0.0, 0.0),
+            rotation: glm::quat_identity(),
+            scale: glm::vec3(1.0, 1.0, 1.0),
+        }
+    }
+}
+
+impl Pose {
+    /// `factor` of 0 gives `self`, 1 gives `other`.
+    pub fn blend(&self, other: &Pose, factor: f32) -> Pose {
+        Pose {
+            translation: glm::lerp(&self.translation, &other.translation, factor),
+            rotation: glm::quat_slerp(&self.rotation, &other.rotation, factor),
+            scale: glm::lerp(&self.scale, &other.scale, factor),
+        }
+    }
+
+    /// Assumes the matrix has no shear or negative scale.
+    pub fn from_matrix(matrix: &glm::Mat4) -> Pose {
+        let column = |i: usize| glm::vec3(matrix[(0, i)], matrix[(1, i)], matrix[(2, i)]);
+        let scale = glm::vec3(column(0).norm(), column(1).norm(), column(2).norm());
+        let axis = |i: usize| {
+            if scale[i] > f32::EPSILON {
+                column(i) / scale[i]
+            } else {
+                glm::Vec3::zeros()
+            }
+        };
+        Pose {
+            translation: column(3),
+            rotation: glm::mat3_to_quat(&glm::Mat3::from_columns(&[axis(0), axis(1), axis(2)])),
+            scale,
+        }
+    }
+
+    pub fn to_matrix(&self) -> glm::Mat4 {
+        glm::translation(&self.translation)
+            * glm::quat_to_mat4(&self.rotation)
+            * glm::scaling(&self.scale)
+    }
+}
+
+/// Keyframes for a single node, times are in seconds from the start of the clip.
+pub struct Channel {
+    pub node: NodeId,
+    pub translations: Vec<(f32, glm::Vec3)>,
+    pub rotations: Vec<(f32, glm::Quat)>,
+    pub scales: Vec<(f32, glm::Vec3)>,
+}
+
+impl Channel {
+    /// Parts without any keyframes are taken from `rest`.
+    pub fn sample(&self, time: f32, rest: &Pose) -> Pose {
+        Pose {
+            translation: sample_keys(&self.translations, time, glm::lerp)
+                .unwrap_or(rest.translation),
+            rotation: sample_keys(&self.rotations, time, glm::quat_slerp).unwrap_or(rest.rotation),
+            scale: sample_keys(&self.scales, time, glm::lerp).unwrap_or(rest.scale),
+        }
+    }
+}
+
+/// Interpolates between the keys either side of `time`, holding the first and last values.
+fn sample_keys<T: Copy>(
+    keys: &[(f32, T)],
+    time: f32,
+    interpolate: impl Fn(&T, &T, f32) -> T,
+) -> Option<T> {
+    let next = keys.partition_point(|(key_time, _)| *key_time <= time);
+    match next {
+        0 => keys.first().map(|(_, value)| *value),
+        next if next == keys.len() => keys.last().map(|(_, value)| *value),
+        next => {
+            let (start_time, start) = &keys[next - 1];
+            let (end_time, end) = &keys[next];
+            let factor = (time - start_time) / (end_time - start_time);
+            Some(interpolate(start, end, factor))
+        }
+    }
+}
+
+pub struct AnimationClip {
+    pub name: String,
+    /// In seconds.
+    pub duration: f32,
+    pub channels: Vec<Channel>,
+}
+
+impl AnimationClip {
+    /// Parts a channel doesn't animate are taken from the node's rest transform in `graph`.
+    pub fn sample<'a>(
+        &'a self,
+        time: f32,
+        graph: &'a SceneGraph,
+    ) -> impl Iterator<Item = (NodeId, Pose)> + 'a {
+        self.channels.iter().map(move |channel| {
+            let rest = graph.node(channel.node).map_or_else(Pose::default, |node| {
+                Pose::from_matrix(node.rest_transform())
+            });
+            (channel.node, channel.sample(time, &rest))
+        })
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Playback {
+    clip: usize,
+    time: f32,
+    looping: bool,
+}
+
+impl Playback {
+    fn advance(&mut self, clips: &[AnimationClip], seconds: f32) {
+        let duration = clips.get(self.clip).map_or(0.0, |clip| clip.duration);
+        self.time += seconds;
+        self.time = if self.looping && duration > 0.0 {
+            self.time.rem_euclid(duration)
+        } else {
+            self.time.clamp(0.0, duration)
+        };
+    }
+}
+
+struct Fade {
+    from: Playback,
+    elapsed: f32,
+    duration: f32,
+}
+
+/// Plays clips on a [`SceneGraph`], optionally cross-fading from the previous clip.
+pub struct AnimationPlayer {
+    current: Option<Playback>,
+    fade: Option<Fade>,
+    pub speed: f32,
+}
+
+impl Default for AnimationPlayer {
+    fn default() -> Self {
+        AnimationPlayer {
+            current: None,
+            fade: None,
+            speed: 1.0,
+        }
+    }
+}
+
+impl AnimationPlayer {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Switches to `clip` immediately, starting from the beginning.
+    pub fn play(&mut self, clip: usize, looping: bool) {
+        self.current = Some(Playback {
+            clip,
+            time: 0.0,
+            looping,
+        });
+        self.fade = None;
+    }
+
+    /// Starts `clip` and blends into it from whatever was playing over `seconds`.
+    pub fn cross_fade(&mut self, clip: usize, looping: bool, seconds: f32) {
+        let from = self.current.take();
+        self.play(clip, looping);
+        if seconds > 0.0 {
+            self.fade = from.map(|from| Fade {
+                from,
+                elapsed: 0.0,
+                duration: seconds,
+            });
+        }
+    }
+
+    pub fn stop(&mut self) {
+        self.current = None;
+        self.fade = None;
+    }
+
+    pub fn clip(&self) -> Option<usize> {
+        self.current.map(|playback| playback.clip)
+    }
+
+    /// Seconds into the current clip.
+    pub fn time(&self) -> f32 {
+        self.current.map_or(0.0, |playback| playback.time)
+    }
+
+    /// Whether a non-looping clip has reached its end.
+    pub fn is_finished(&self, clips: &[AnimationClip]) -> bool {
+        self.current.is_none_or(|playback| {
+            !playback.looping
+                && clips
+                    .get(playback.clip)
+                    .is_none_or(|clip| playback.time >= clip.duration)
+        })
+    }
+
+    pub fn advance(&mut self, clips: &[AnimationClip], seconds: f32) {
+        let seconds = seconds * self.speed;
+        if let Some(current) = &mut self.current {
+            current.advance(clips, seconds);
+        }
+        if let Some(fade) = &mut self.fade {
+            fade.from.advance(clips, seconds);
+            fade.elapsed += seconds.abs();
+            if fade.elapsed >= fade.duration {
+                self.fade = None;
+            }
+        }
+    }
+
+    /// Poses the nodes animated by the playing clips and updates the world transforms. Nodes
+    /// animated by any of `clips` but not by what's playing go back to their rest transform.
+    pub fn apply(&self, clips: &[AnimationClip], graph: &mut SceneGraph) {
+        let mut poses: HashMap<NodeId, Pose> = match &self.fade {
+            Some(fade) => clips
+                .get(fade.from.clip)
+                .map(|from| from.sample(fade.from.time, graph).collect())
+                .unwrap_or_default(),
+            None => Default::default(),
+        };
+        let factor = self
+            .fade
+            .as_ref()
+            .map_or(1.0, |fade| fade.elapsed / fade.duration);
+
+        let current = self
+            .current
+            .and_then(|current| Some((clips.get(current.clip)?, current.time)));
+        if let Some((clip, time)) = current {
+            for (node, pose) in clip.sample(time, graph) {
+                let pose = match poses.get(&node) {
+                    Some(from) => from.blend(&pose, factor),
+                    None => pose,
+                };
+                poses.insert(node, pose);
+            }
+        }
+
+        let animated: HashSet<NodeId> = clips
+            .iter()
+            .flat_map(|clip| &clip.channels)
+            .map(|channel| channel.node)
+            .collect();
+        for node in animated {
+            let local = match poses.get(&node) {
+                Some(pose) => pose.to_matrix(),
+                None => match graph.node(node) {
+                    Some(node) => *node.rest_transform(),
+                    None => continue,
+                },
+            };
+            graph.set_local_transform(node, local);
+        }
+        graph.update_transforms();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn approx(a: &glm::Mat4, b: &glm::Mat4) -> bool {
+        (a - b).iter().all(|value| value.abs() < 1e-5)
+    }
+
+    #[test]
+    fn sample_keys_interpolates_and_holds_the_ends() {
+        let keys = [(1.0, 10.0), (2.0, 20.0), (4.0, 0.0)];
+        let lerp = |a: &f32, b: &f32, t: f32| a + (b - a) * t;
+        assert_eq!(sample_keys(&keys, 0.0, lerp), Some(10.0));
+        assert_eq!(sample_keys(&keys, 1.5, lerp), Some(15.0));
+        assert_eq!(sample_keys(&keys, 3.0, lerp), Some(10.0));
+        assert_eq!(sample_keys(&keys, 5.0, lerp), Some(0.0));
+        assert_eq!(sample_keys::<f32>(&[], 1.0, lerp), None);
+    }
+
+    #[test]
+    fn pose_round_trips_through_a_matrix() {
+        let pose = Pose {
+            translation: glm::vec3(1.0, 2.0, 3.0),
+            rotation: glm::quat_angle_axis(0.7, &glm::normalize(&glm::vec3(1.0, 1.0, 0.0))),
+            scale: glm::vec3(2.0, 0.5, 1.0),
+        };
+        let matrix = pose.to_matrix();
+        assert!(approx(&Pose::from_matrix(&matrix).to_matrix(), &matrix));
+    }
+
+    #[test]
+    fn channels_fall_back_to_the_rest_pose() {
+        let rest = Pose {
+            translation: glm::vec3(0.0, 5.0, 0.0),
+            scale: glm::vec3(3.0, 3.0, 3.0),
+            ..Default::default()
+        };
+        let channel = Channel {
+            node: 0,
+            translations: Vec::new(),
+            rotations: vec![(0.0, glm::quat_identity())],
+            scales: Vec::new(),
+        };
+        let pose = channel.sample(1.0, &rest);
+        assert_eq!(pose.translation, rest.translation);
+        assert_eq!(pose.scale, rest.scale);
+    }
+
+    #[test]
+    fn stopping_restores_the_rest_transform() {
+        let mut graph = SceneGraph::new();
+        let rest = glm::translation(&glm::vec3(0.0, 1.0, 0.0));
+        let root = graph.add_node(None, "root", rest, Vec::new());
+        let clips = [AnimationClip {
+            name: "slide".to_string(),
+            duration: 1.0,
+            channels: vec![Channel {
+                node: root,
+                translations: vec![
+                    (0.0, glm::vec3(0.0, 1.0, 0.0)),
+                    (1.0, glm::vec3(4.0, 1.0, 0.0)),
+                ],
+                rotations: Vec::new(),
+                scales: Vec::new(),
+            }],
+        }];
+
+        let mut player = AnimationPlayer::new();
+        player.play(0, false);
+        player.advance(&clips, 0.5);
+        player.apply(&clips, &mut graph);
+        let posed = glm::translation(&glm::vec3(2.0, 1.0, 0.0));
+        assert!(approx(graph.node(root).unwrap().world_transform(), &posed));
+
+        player.stop();
+        player.apply(&clips, &mut graph);
+        assert!(approx(graph.node(root).unwrap().world_transform(), &rest));
+    }
+
+    #[test]
+    fn palette_is_the_identity_in_the_bind_pose() {
+        let mut graph = SceneGraph::new();
+        let bind = glm::translation(&glm::vec3(1.0, 2.0, 3.0));
+        let joint = graph.add_node(None, "joint", bind, Vec::new());
+        let skeleton = Skeleton {
+            bones: vec![
+                Bone {
+                    name: "joint".to_string(),
+                    node: Some(joint),
+                    offset: glm::inverse(&bind),
+                },
+                Bone {
+                    name: "missing".to_string(),
+                    node: None,
+                    offset: bind,
+                },
+            ],
+        };
+        for matrix in skeleton.palette(&graph) {
+            assert!(approx(&matrix, &glm::identity()));
+        }
+    }
+}
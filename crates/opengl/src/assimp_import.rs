@@ -0,0 +1,266 @@
+use crate::animation::*;
+use crate::error::*;
+use crate::model::*;
+use crate::scene_graph::*;
+use nalgebra_glm as glm;
+use std::path::{Path, PathBuf};
+
+impl SceneData {
+    /// Imports any file format assimp supports. Missing normals are generated, missing
+    /// texture coordinates are zeroed and duplicate vertices are welded.
+    pub fn import_assimp<P: AsRef<Path>>(path: P) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let scene = russimp::scene::Scene::from_file(
+            &path.to_string_lossy(),
+            vec![
+                russimp::scene::PostProcess::FlipUVs,
+                russimp::scene::PostProcess::Triangulate,
+                russimp::scene::PostProcess::GenerateSmoothNormals,
+            ],
+        )?;
+
+        let parent_path = path.parent().unwrap_or(Path::new(""));
+
+        let mut texture_filenames: Vec<PathBuf> = Vec::with_capacity(2 * scene.materials.len());
+        let mut texture_map = |filename: Option<PathBuf>| {
+            let filename = filename?;
+            let index = texture_filenames
+                .iter()
+                .position(|existing| *existing == filename)
+                .unwrap_or_else(|| {
+                    texture_filenames.push(filename);
+                    texture_filenames.len() - 1
+                });
+            Some(TextureMap::new(index))
+        };
+
+        let materials: Vec<_> = scene
+            .materials
+            .iter()
+            .map(|material| Material {
+                diffuse: texture_map(find_texture(
+                    parent_path,
+                    &material.properties,
+                    russimp::material::TextureType::Diffuse,
+                )),
+                specular: texture_map(find_texture(
+                    parent_path,
+                    &material.properties,
+                    russimp::material::TextureType::Specular,
+                )),
+                normal: texture_map(find_texture(
+                    parent_path,
+                    &material.properties,
+                    russimp::material::TextureType::Normals,
+                )),
+                ..Default::default()
+            })
+            .collect();
+
+        let mut graph = SceneGraph::new();
+        match &scene.root {
+            Some(root) => import_node(&mut graph, None, root),
+            None => {
+                graph.add_node(None, "", glm::identity(), (0..scene.meshes.len()).collect());
+            }
+        }
+
+        let mut skeleton = Skeleton::default();
+        let mut meshes = Vec::with_capacity(scene.meshes.len());
+        for mesh in &scene.meshes {
+            let material_index = mesh.material_index as usize;
+            if materials.len() <= material_index {
+                return Err(Error::InvalidMaterialIndex(material_index, materials.len()).into());
+            }
+
+            let indices = mesh
+                .faces
+                .iter()
+                .flat_map(|face| face.0.iter().copied())
+                .collect();
+
+            let uvs = mesh.texture_coords.first().and_then(Option::as_ref);
+            let vertices = mesh
+                .vertices
+                .iter()
+                .enumerate()
+                .map(|(i, position)| {
+                    let [nx, ny, nz] = mesh.normals.get(i).map_or([0.0; 3], |n| [n.x, n.y, n.z]);
+                    let [u, v] = uvs
+                        .and_then(|uvs| uvs.get(i))
+                        .map_or([0.0; 2], |uv| [uv.x, uv.y]);
+                    [position.x, position.y, position.z, nx, ny, nz, u, v]
+                })
+                .collect();
+
+            let mut mesh = MeshData {
+                vertices,
+                indices,
+                material_index,
+                skin: import_skin(mesh, &graph, &mut skeleton),
+                tangents: None,
+                second_uvs: None,
+            };
+            mesh.check_indices()?;
+            // Without JoinIdenticalVertices assimp gives every face its own vertices
+            mesh.weld(0.0);
+            meshes.push(mesh);
+        }
+        if skeleton.bones.len() > MAX_BONES {
+            return Err(Error::TooManyBones(skeleton.bones.len(), MAX_BONES).into());
+        }
+
+        let animations = scene
+            .animations
+            .iter()
+            .map(|animation| import_animation(animation, &graph))
+            .collect();
+
+        let mut scene = SceneData {
+            textures: texture_filenames
+                .into_iter()
+                .map(TextureSource::File)
+                .collect(),
+            materials,
+            meshes,
+            graph,
+            skeleton,
+            animations,
+        };
+        scene.process_meshes()?;
+        Ok(scene)
+    }
+}
+
+fn import_matrix(m: &russimp::Matrix4x4) -> glm::Mat4 {
+    glm::Mat4::new(
+        m.a1, m.a2, m.a3, m.a4, m.b1, m.b2, m.b3, m.b4, m.c1, m.c2, m.c3, m.c4, m.d1, m.d2, m.d3,
+        m.d4,
+    )
+}
+
+/// Keeps the strongest influences on each vertex, bones without a matching node are ignored.
+fn import_skin(
+    mesh: &russimp::mesh::Mesh,
+    graph: &SceneGraph,
+    skeleton: &mut Skeleton,
+) -> Option<Vec<[f32; 8]>> {
+    if mesh.bones.is_empty() {
+        return None;
+    }
+
+    let mut influences = vec![Vec::<(usize, f32)>::new(); mesh.vertices.len()];
+    for bone in &mesh.bones {
+        let Some(node) = graph.find(&bone.name) else {
+            continue;
+        };
+        let index = skeleton.find(&bone.name).unwrap_or_else(|| {
+            skeleton.bones.push(Bone {
+                name: bone.name.clone(),
+                node: Some(node),
+                offset: import_matrix(&bone.offset_matrix),
+            });
+            skeleton.bones.len() - 1
+        });
+        for weight in &bone.weights {
+            if let Some(influences) = influences.get_mut(weight.vertex_id as usize) {
+                influences.push((index, weight.weight));
+            }
+        }
+    }
+
+    let skin = influences
+        .into_iter()
+        .map(|mut influences| {
+            influences.sort_by(|a, b| b.1.total_cmp(&a.1));
+            influences.truncate(MAX_BONE_INFLUENCES);
+            let total: f32 = influences.iter().map(|(_, weight)| weight).sum();
+
+            let mut skin = [0.0; 8];
+            for (i, (index, weight)) in influences.into_iter().enumerate() {
+                skin[i] = index as f32;
+                skin[MAX_BONE_INFLUENCES + i] = if total > 0.0 { weight / total } else { 0.0 };
+            }
+            skin
+        })
+        .collect();
+    Some(skin)
+}
+
+/// Converts key times from ticks to seconds, channels without a matching node are dropped.
+fn import_animation(
+    animation: &russimp::animation::Animation,
+    graph: &SceneGraph,
+) -> AnimationClip {
+    // Assimp leaves this at zero when the file doesn't say
+    let ticks_per_second = if animation.ticks_per_second > 0.0 {
+        animation.ticks_per_second
+    } else {
+        25.0
+    };
+    let seconds = |ticks: f64| (ticks / ticks_per_second) as f32;
+    let vector = |v: &russimp::Vector3D| glm::vec3(v.x, v.y, v.z);
+
+    let channels = animation
+        .channels
+        .iter()
+        .filter_map(|channel| {
+            Some(Channel {
+                node: graph.find(&channel.name)?,
+                translations: channel
+                    .position_keys
+                    .iter()
+                    .map(|key| (seconds(key.time), vector(&key.value)))
+                    .collect(),
+                rotations: channel
+                    .rotation_keys
+                    .iter()
+                    .map(|key| {
+                        let q = &key.value;
+                        (seconds(key.time), glm::quat(q.x, q.y, q.z, q.w))
+                    })
+                    .collect(),
+                scales: channel
+                    .scaling_keys
+                    .iter()
+                    .map(|key| (seconds(key.time), vector(&key.value)))
+                    .collect(),
+            })
+        })
+        .collect();
+
+    AnimationClip {
+        name: animation.name.clone(),
+        duration: seconds(animation.duration),
+        channels,
+    }
+}
+
+fn import_node(graph: &mut SceneGraph, parent: Option<NodeId>, node: &russimp::node::Node) {
+    let meshes = node.meshes.iter().map(|&index| index as usize).collect();
+    let id = graph.add_node(
+        parent,
+        &node.name,
+        import_matrix(&node.transformation),
+        meshes,
+    );
+    for child in node.children.borrow().iter() {
+        import_node(graph, Some(id), child);
+    }
+}
+
+fn find_texture(
+    parent_path: &Path,
+    properties: &[russimp::material::MaterialProperty],
+    target: russimp::material::TextureType,
+) -> Option<PathBuf> {
+    let property = properties
+        .iter()
+        .find(|property| property.key == "$tex.file" && property.semantic == target)?;
+
+    if let russimp::material::PropertyTypeInfo::String(filename) = &property.data {
+        Some(parent_path.join(filename))
+    } else {
+        None
+    }
+}
@@ -8,4 +8,28 @@ pub enum Error {
     ProgramLink(String),
     #[error("Could not find uniform with name: {0}")]
     MissingUniform(String),
+    #[error("Model '{0}' needs the assimp feature, only glTF is supported without it")]
+    UnsupportedModelFormat(std::path::PathBuf),
+    #[error("Mesh index {0} is out of range for its {1} vertices")]
+    InvalidVertexIndex(u32, usize),
+    #[error("Mesh uses material {0}, but the model only has {1}")]
+    InvalidMaterialIndex(usize, usize),
+    #[error("Model has {0} bones, but at most {1} are supported")]
+    TooManyBones(usize, usize),
+    #[error("Failed to generate tangents, the mesh may have no triangles")]
+    TangentGeneration,
+    #[error("Asset loader stopped before the asset finished loading")]
+    AssetLoadAborted,
+    #[error("Asset loader job panicked: {0}")]
+    AssetLoadPanicked(String),
+    #[error("Program uses {0} samplers, but only {1} texture units are available")]
+    TooManyTextureUnits(usize, usize),
+    #[error("Texture region (x, y, width, height) {0:?} exceeds texture size {1:?}")]
+    TextureRegionOutOfBounds((u32, u32, u32, u32), (u32, u32)),
+    #[error("Texture data has {0} bytes, but the region needs {1}")]
+    TextureDataSize(usize, usize),
+    #[error("Frame buffer is incomplete, status: {0:#x}")]
+    IncompleteFrameBuffer(u32),
+    #[error("Requested {0} samples, but between 1 and {1} are supported")]
+    UnsupportedSampleCount(u32, u32),
 }
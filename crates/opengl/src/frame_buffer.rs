@@ -0,0 +1,247 @@
+use crate::error::*;
+use crate::texture::*;
+
+pub use opengl_sys::{BlitFilter, BufferBit, RenderBufferStorageFormat};
+
+/// Storage for an attachment that is only ever rendered to, never sampled.
+pub struct RenderBuffer {
+    id: opengl_sys::RenderBufferID,
+    format: RenderBufferStorageFormat,
+    samples: Option<u32>,
+    width: u32,
+    height: u32,
+}
+
+impl RenderBuffer {
+    pub fn new(width: u32, height: u32, format: RenderBufferStorageFormat) -> anyhow::Result<Self> {
+        let mut render_buffer = Self {
+            id: opengl_sys::create_render_buffer(),
+            format,
+            samples: None,
+            width,
+            height,
+        };
+        render_buffer.resize(width, height)?;
+        Ok(render_buffer)
+    }
+
+    pub fn new_multisample(
+        width: u32,
+        height: u32,
+        format: RenderBufferStorageFormat,
+        samples: u32,
+    ) -> anyhow::Result<Self> {
+        let mut render_buffer = Self {
+            id: opengl_sys::create_render_buffer(),
+            format,
+            samples: Some(check_sample_count(samples)?),
+            width,
+            height,
+        };
+        render_buffer.resize(width, height)?;
+        Ok(render_buffer)
+    }
+
+    /// Reallocates storage at the new size, the previous contents are discarded.
+    pub fn resize(&mut self, width: u32, height: u32) -> anyhow::Result<()> {
+        opengl_sys::bind_render_buffer(self.id)?;
+        match self.samples {
+            Some(samples) => {
+                opengl_sys::render_buffer_storage_multisample(samples, self.format, width, height)?
+            }
+            None => opengl_sys::render_buffer_storage(self.format, width, height)?,
+        }
+        opengl_sys::bind_render_buffer(0)?;
+        self.width = width;
+        self.height = height;
+        Ok(())
+    }
+
+    pub fn attach_to_frame_buffer(
+        &self,
+        target: FrameBufferTarget,
+        attachment: FrameBufferAttachment,
+    ) -> anyhow::Result<()> {
+        opengl_sys::frame_buffer_render_buffer(target, attachment, self.id)?;
+        Ok(())
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+}
+
+impl Drop for RenderBuffer {
+    fn drop(&mut self) {
+        opengl_sys::delete_render_buffer(self.id).expect("Failed to delete render buffer");
+    }
+}
+
+/// An off-screen render target. Attach textures or render buffers while it is bound.
+pub struct FrameBuffer {
+    id: opengl_sys::FrameBufferID,
+}
+
+impl FrameBuffer {
+    pub fn new() -> Self {
+        Self {
+            id: opengl_sys::create_frame_buffer(),
+        }
+    }
+
+    pub fn bind(&self) -> anyhow::Result<()> {
+        opengl_sys::bind_frame_buffer(self.id, FrameBufferTarget::All)?;
+        Ok(())
+    }
+
+    /// Goes back to drawing into the window.
+    pub fn bind_default() -> anyhow::Result<()> {
+        opengl_sys::bind_frame_buffer(0, FrameBufferTarget::All)?;
+        Ok(())
+    }
+
+    /// Binds the frame buffer, then routes fragment output `i` to `colour_attachments[i]`.
+    pub fn set_draw_buffers(&self, colour_attachments: &[u32]) -> anyhow::Result<()> {
+        self.bind()?;
+        opengl_sys::set_draw_buffers(colour_attachments)?;
+        Ok(())
+    }
+
+    /// Copies `buffers` into `destination`, or the window when `None`, resolving multisampled
+    /// attachments on the way. Leaves `destination` bound for drawing.
+    pub fn blit_to(
+        &self,
+        destination: Option<&FrameBuffer>,
+        source_size: (u32, u32),
+        destination_size: (u32, u32),
+        buffers: &[BufferBit],
+        filter: BlitFilter,
+    ) -> anyhow::Result<()> {
+        opengl_sys::bind_frame_buffer(self.id, FrameBufferTarget::Read)?;
+        opengl_sys::bind_frame_buffer(
+            destination.map_or(0, |destination| destination.id),
+            FrameBufferTarget::Draw,
+        )?;
+        opengl_sys::blit_frame_buffer(
+            (0, 0, source_size.0 as _, source_size.1 as _),
+            (0, 0, destination_size.0 as _, destination_size.1 as _),
+            buffers,
+            filter,
+        )?;
+        Ok(())
+    }
+
+    /// Binds the frame buffer and checks every attachment is usable.
+    pub fn check_complete(&self) -> anyhow::Result<()> {
+        self.bind()?;
+        match opengl_sys::check_frame_buffer_status(FrameBufferTarget::All)? {
+            Some(status) => Err(Error::IncompleteFrameBuffer(status).into()),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Default for FrameBuffer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for FrameBuffer {
+    fn drop(&mut self) {
+        opengl_sys::delete_frame_buffer(self.id).expect("Failed to delete frame buffer");
+    }
+}
+
+/// Anti-aliased off-screen target for post-processing: draw into it after [`Self::bind`], then
+/// [`Self::resolve`] averages the samples into a regular texture that shaders can filter.
+pub struct MultisampleRenderTarget {
+    multisampled: FrameBuffer,
+    colour: TextureImage2DMultisample,
+    depth_stencil: RenderBuffer,
+    resolved: FrameBuffer,
+    texture: TextureImage2D,
+}
+
+impl MultisampleRenderTarget {
+    pub fn new(
+        width: u32,
+        height: u32,
+        format: TextureFormat,
+        samples: u32,
+    ) -> anyhow::Result<Self> {
+        let target = Self {
+            multisampled: FrameBuffer::new(),
+            colour: TextureImage2DMultisample::new_empty(width, height, format, samples)?,
+            depth_stencil: RenderBuffer::new_multisample(
+                width,
+                height,
+                RenderBufferStorageFormat::Depth24Stencil8,
+                samples,
+            )?,
+            resolved: FrameBuffer::new(),
+            texture: TextureImage2D::new_empty(width, height, format)?,
+        };
+
+        target.multisampled.bind()?;
+        target
+            .colour
+            .attach_to_frame_buffer(FrameBufferTarget::All, FrameBufferAttachment::Colour(0))?;
+        target
+            .depth_stencil
+            .attach_to_frame_buffer(FrameBufferTarget::All, FrameBufferAttachment::DepthStencil)?;
+        target.multisampled.check_complete()?;
+
+        target.resolved.bind()?;
+        target
+            .texture
+            .attach_to_frame_buffer(FrameBufferTarget::All, FrameBufferAttachment::Colour(0))?;
+        target.resolved.check_complete()?;
+        FrameBuffer::bind_default()?;
+
+        Ok(target)
+    }
+
+    /// Call whenever the window changes size, a no-op if it hasn't.
+    pub fn resize(&mut self, width: u32, height: u32) -> anyhow::Result<()> {
+        if self.texture.size() == (width, height) {
+            return Ok(());
+        }
+
+        self.colour.resize(width, height)?;
+        self.depth_stencil.resize(width, height)?;
+        self.texture.resize(width, height)?;
+        Ok(())
+    }
+
+    pub fn bind(&self) -> anyhow::Result<()> {
+        self.multisampled.bind()
+    }
+
+    /// Averages the samples into [`Self::texture`] and goes back to drawing into the window.
+    pub fn resolve(&self) -> anyhow::Result<()> {
+        let size = self.texture.size();
+        self.multisampled.blit_to(
+            Some(&self.resolved),
+            size,
+            size,
+            &[BufferBit::Colour],
+            BlitFilter::Nearest,
+        )?;
+        FrameBuffer::bind_default()
+    }
+
+    /// The resolved colour, only up to date after [`Self::resolve`].
+    pub fn texture(&self) -> &TextureImage2D {
+        &self.texture
+    }
+
+    /// The individual samples, for custom resolves through a `sampler2DMS`.
+    pub fn multisampled_texture(&self) -> &TextureImage2DMultisample {
+        &self.colour
+    }
+
+    pub fn samples(&self) -> u32 {
+        self.colour.samples()
+    }
+}
@@ -0,0 +1,446 @@
+use crate::animation::*;
+use crate::error::*;
+use crate::mesh_processing::*;
+use crate::model::*;
+use crate::scene_graph::*;
+use crate::texture::*;
+use nalgebra_glm as glm;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+impl SceneData {
+    /// Imports a glTF 2.0 or GLB file without going through assimp. Textures embedded in the
+    /// file are decoded here, primitives other than triangles are skipped.
+    pub fn import_gltf<P: AsRef<Path>>(path: P) -> anyhow::Result<Self> {
+        let (document, buffers, images) = gltf::import(path)?;
+
+        let mut materials: Vec<_> = document.materials().map(import_material).collect();
+
+        // Base colour and emissive maps are sRGB encoded, the others hold linear data
+        let colour_images: HashSet<usize> = materials
+            .iter()
+            .flat_map(|material| [material.diffuse, material.emissive])
+            .flatten()
+            .map(|map| map.texture)
+            .collect();
+        let textures = images
+            .into_iter()
+            .enumerate()
+            .map(|(index, image)| {
+                let image = convert_image(image);
+                TextureSource::Image(if colour_images.contains(&index) {
+                    image.into_srgb()
+                } else {
+                    image
+                })
+            })
+            .collect();
+
+        // Primitives without a material use the glTF default one
+        let default_material = materials.len();
+        materials.push(Material::default());
+
+        let mut meshes = Vec::new();
+        let mut primitives = Vec::with_capacity(document.meshes().len());
+        for mesh in document.meshes() {
+            let first = meshes.len();
+            for primitive in mesh.primitives() {
+                if primitive.mode() != gltf::mesh::Mode::Triangles {
+                    continue;
+                }
+                let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
+                let Some(positions) = reader.read_positions() else {
+                    continue;
+                };
+                let positions: Vec<[f32; 3]> = positions.collect();
+                let normals: Vec<[f32; 3]> = reader
+                    .read_normals()
+                    .map(|normals| normals.collect())
+                    .unwrap_or_default();
+                let uvs: Vec<[f32; 2]> = reader
+                    .read_tex_coords(0)
+                    .map(|uvs| uvs.into_f32().collect())
+                    .unwrap_or_default();
+
+                let vertices = positions
+                    .iter()
+                    .enumerate()
+                    .map(|(i, position)| {
+                        let [nx, ny, nz] = normals.get(i).copied().unwrap_or_default();
+                        let [u, v] = uvs.get(i).copied().unwrap_or_default();
+                        [position[0], position[1], position[2], nx, ny, nz, u, v]
+                    })
+                    .collect();
+
+                let skin = match (reader.read_joints(0), reader.read_weights(0)) {
+                    (Some(joints), Some(weights)) => Some(
+                        joints
+                            .into_u16()
+                            .zip(weights.into_f32())
+                            .map(|(joints, weights)| {
+                                let mut skin = [0.0; 8];
+                                for i in 0..MAX_BONE_INFLUENCES {
+                                    skin[i] = joints[i] as f32;
+                                    skin[MAX_BONE_INFLUENCES + i] = weights[i];
+                                }
+                                skin
+                            })
+                            .collect(),
+                    ),
+                    _ => None,
+                };
+
+                let mut mesh = MeshData {
+                    vertices,
+                    indices: reader
+                        .read_indices()
+                        .map(|indices| indices.into_u32().collect())
+                        .unwrap_or_else(|| (0..positions.len() as u32).collect()),
+                    material_index: primitive.material().index().unwrap_or(default_material),
+                    skin,
+                    tangents: reader.read_tangents().map(|tangents| tangents.collect()),
+                    second_uvs: reader
+                        .read_tex_coords(1)
+                        .map(|uvs| uvs.into_f32().collect()),
+                };
+                mesh.check_indices()?;
+                // The spec asks for flat normals when they're missing
+                if normals.is_empty() {
+                    mesh.recompute_normals(NormalMode::Flat);
+                    mesh.weld(0.0);
+                }
+                meshes.push(mesh);
+            }
+            primitives.push(first..meshes.len());
+        }
+
+        let scene = document
+            .default_scene()
+            .or_else(|| document.scenes().next());
+        let mut graph = SceneGraph::new();
+        let mut node_ids = vec![None; document.nodes().len()];
+        for node in scene.iter().flat_map(|scene| scene.nodes()) {
+            import_node(&mut graph, &mut node_ids, &primitives, None, &node);
+        }
+
+        let mut skeleton = Skeleton::default();
+        let mut skin_offsets = HashMap::new();
+        for skin in document.skins() {
+            let offset = skeleton.bones.len();
+            let reader = skin.reader(|buffer| Some(&buffers[buffer.index()]));
+            let mut inverse_bind_matrices =
+                reader.read_inverse_bind_matrices().into_iter().flatten();
+            for joint in skin.joints() {
+                let offset = inverse_bind_matrices
+                    .next()
+                    .map_or_else(glm::identity, glm::Mat4::from);
+                // Joints outside the scene keep their slot so vertex indices stay valid
+                skeleton.bones.push(Bone {
+                    name: joint.name().unwrap_or_default().to_string(),
+                    node: node_ids[joint.index()],
+                    offset,
+                });
+            }
+            skin_offsets.insert(skin.index(), offset);
+        }
+        if skeleton.bones.len() > MAX_BONES {
+            return Err(Error::TooManyBones(skeleton.bones.len(), MAX_BONES).into());
+        }
+
+        // Joint indices are relative to the skin, so move them into the shared skeleton using
+        // the first node that draws each mesh with a skin
+        let mut offset_meshes = vec![false; meshes.len()];
+        for node in document.nodes() {
+            let (Some(mesh), Some(skin)) = (node.mesh(), node.skin()) else {
+                continue;
+            };
+            let offset = skin_offsets[&skin.index()] as f32;
+            for index in primitives[mesh.index()].clone() {
+                let Some(skin) = &mut meshes[index].skin else {
+                    continue;
+                };
+                if std::mem::replace(&mut offset_meshes[index], true) {
+                    continue;
+                }
+                for vertex in skin.iter_mut() {
+                    for joint in &mut vertex[..MAX_BONE_INFLUENCES] {
+                        *joint += offset;
+                    }
+                }
+            }
+        }
+
+        let animations = document
+            .animations()
+            .map(|animation| import_animation(&animation, &buffers, &node_ids))
+            .collect();
+
+        let mut scene = SceneData {
+            textures,
+            materials,
+            meshes,
+            graph,
+            skeleton,
+            animations,
+        };
+        scene.process_meshes()?;
+        Ok(scene)
+    }
+}
+
+fn import_node(
+    graph: &mut SceneGraph,
+    node_ids: &mut [Option<NodeId>],
+    primitives: &[std::ops::Range<usize>],
+    parent: Option<NodeId>,
+    node: &gltf::Node,
+) {
+    let meshes = node
+        .mesh()
+        .map(|mesh| primitives[mesh.index()].clone().collect())
+        .unwrap_or_default();
+    let id = graph.add_node(
+        parent,
+        node.name().unwrap_or_default(),
+        glm::Mat4::from(node.transform().matrix()),
+        meshes,
+    );
+    node_ids[node.index()] = Some(id);
+    for child in node.children() {
+        import_node(graph, node_ids, primitives, Some(id), &child);
+    }
+}
+
+fn import_material(material: gltf::Material) -> Material {
+    let pbr = material.pbr_metallic_roughness();
+    let normal = material.normal_texture();
+    let occlusion = material.occlusion_texture();
+
+    Material {
+        diffuse: pbr.base_color_texture().as_ref().map(texture_map),
+        metallic_roughness: pbr.metallic_roughness_texture().as_ref().map(texture_map),
+        normal: normal.as_ref().map(|normal| {
+            extension_texture_map(
+                normal.texture(),
+                normal.tex_coord(),
+                normal.extension_value("KHR_texture_transform"),
+            )
+        }),
+        occlusion: occlusion.as_ref().map(|occlusion| {
+            extension_texture_map(
+                occlusion.texture(),
+                occlusion.tex_coord(),
+                occlusion.extension_value("KHR_texture_transform"),
+            )
+        }),
+        emissive: material.emissive_texture().as_ref().map(texture_map),
+        base_colour_factor: pbr.base_color_factor(),
+        metallic_factor: pbr.metallic_factor(),
+        roughness_factor: pbr.roughness_factor(),
+        emissive_factor: material.emissive_factor(),
+        normal_scale: normal.as_ref().map_or(1.0, |normal| normal.scale()),
+        occlusion_strength: occlusion
+            .as_ref()
+            .map_or(1.0, |occlusion| occlusion.strength()),
+        ..Default::default()
+    }
+}
+
+fn texture_map(info: &gltf::texture::Info) -> TextureMap {
+    let mut map = TextureMap::new(info.texture().source().index());
+    map.uv_set = info.tex_coord();
+    if let Some(transform) = info.texture_transform() {
+        map.transform = TextureTransform {
+            offset: transform.offset(),
+            rotation: transform.rotation(),
+            scale: transform.scale(),
+        };
+        map.uv_set = transform.tex_coord().unwrap_or(map.uv_set);
+    }
+    map
+}
+
+/// Normal and occlusion textures only expose `KHR_texture_transform` as raw JSON.
+fn extension_texture_map(
+    texture: gltf::Texture,
+    tex_coord: u32,
+    transform: Option<&gltf::json::Value>,
+) -> TextureMap {
+    let mut map = TextureMap::new(texture.source().index());
+    map.uv_set = tex_coord;
+    let transform = transform.and_then(|transform| {
+        gltf::json::deserialize::from_value::<gltf::json::extensions::texture::TextureTransform>(
+            transform.clone(),
+        )
+        .ok()
+    });
+    if let Some(transform) = transform {
+        map.transform = TextureTransform {
+            offset: transform.offset.0,
+            rotation: transform.rotation.0,
+            scale: transform.scale.0,
+        };
+        map.uv_set = transform.tex_coord.unwrap_or(map.uv_set);
+    }
+    map
+}
+
+/// Converts to 8 bit RGB or RGBA, the formats textures are uploaded in.
+fn convert_image(image: gltf::image::Data) -> ImageData {
+    use gltf::image::Format;
+
+    let (channels, bytes) = match image.format {
+        Format::R8 => (1, 1),
+        Format::R8G8 => (2, 1),
+        Format::R8G8B8 => (3, 1),
+        Format::R8G8B8A8 => (4, 1),
+        Format::R16 => (1, 2),
+        Format::R16G16 => (2, 2),
+        Format::R16G16B16 => (3, 2),
+        Format::R16G16B16A16 => (4, 2),
+        Format::R32G32B32FLOAT => (3, 4),
+        Format::R32G32B32A32FLOAT => (4, 4),
+    };
+    match (channels, bytes) {
+        (3, 1) => ImageData::new(image.pixels, TextureFormat::RGB, image.width, image.height),
+        (4, 1) => ImageData::new(image.pixels, TextureFormat::RGBA, image.width, image.height),
+        _ => {
+            let pixels = image
+                .pixels
+                .chunks_exact(channels * bytes)
+                .flat_map(|pixel| {
+                    let channel = |i: usize| match bytes {
+                        1 => pixel[i],
+                        // Little endian, so the high byte is second
+                        2 => pixel[2 * i + 1],
+                        _ => {
+                            let value =
+                                f32::from_le_bytes(pixel[4 * i..4 * i + 4].try_into().unwrap());
+                            (value.clamp(0.0, 1.0) * 255.0).round() as u8
+                        }
+                    };
+                    match channels {
+                        1 => [channel(0), channel(0), channel(0), 255],
+                        2 => [channel(0), channel(1), 0, 255],
+                        3 => [channel(0), channel(1), channel(2), 255],
+                        _ => [channel(0), channel(1), channel(2), channel(3)],
+                    }
+                })
+                .collect();
+            ImageData::new(pixels, TextureFormat::RGBA, image.width, image.height)
+        }
+    }
+}
+
+/// Channels targeting the same node are merged, properties they leave out come from the rest pose.
+/// Cubic spline tangents are dropped and the keys interpolated linearly.
+fn import_animation(
+    animation: &gltf::Animation,
+    buffers: &[gltf::buffer::Data],
+    node_ids: &[Option<NodeId>],
+) -> AnimationClip {
+    use gltf::animation::util::ReadOutputs;
+
+    let mut channels: Vec<Channel> = Vec::new();
+    let mut duration: f32 = 0.0;
+    for gltf_channel in animation.channels() {
+        let target = gltf_channel.target().node();
+        let Some(node) = node_ids[target.index()] else {
+            continue;
+        };
+        let reader = gltf_channel.reader(|buffer| Some(&buffers[buffer.index()]));
+        let (Some(inputs), Some(outputs)) = (reader.read_inputs(), reader.read_outputs()) else {
+            continue;
+        };
+        let times: Vec<f32> = inputs.collect();
+        duration = times.iter().copied().fold(duration, f32::max);
+
+        let channel = match channels.iter().position(|channel| channel.node == node) {
+            Some(index) => &mut channels[index],
+            None => {
+                channels.push(Channel {
+                    node,
+                    translations: Vec::new(),
+                    rotations: Vec::new(),
+                    scales: Vec::new(),
+                });
+                channels.last_mut().unwrap()
+            }
+        };
+
+        let cubic_spline =
+            gltf_channel.sampler().interpolation() == gltf::animation::Interpolation::CubicSpline;
+        match outputs {
+            ReadOutputs::Translations(translations) => {
+                channel.translations =
+                    keyframes(&times, translations.map(glm::Vec3::from), cubic_spline);
+            }
+            ReadOutputs::Rotations(rotations) => {
+                let rotations = rotations
+                    .into_f32()
+                    .map(|[x, y, z, w]| glm::quat(x, y, z, w));
+                channel.rotations = keyframes(&times, rotations, cubic_spline);
+            }
+            ReadOutputs::Scales(scales) => {
+                channel.scales = keyframes(&times, scales.map(glm::Vec3::from), cubic_spline);
+            }
+            ReadOutputs::MorphTargetWeights(_) => {}
+        }
+    }
+
+    AnimationClip {
+        name: animation.name().unwrap_or_default().to_string(),
+        duration,
+        channels,
+    }
+}
+
+fn keyframes<T>(
+    times: &[f32],
+    values: impl Iterator<Item = T>,
+    cubic_spline: bool,
+) -> Vec<(f32, T)> {
+    // Cubic spline outputs are in-tangent, value, out-tangent triples
+    let values: Box<dyn Iterator<Item = T>> = if cubic_spline {
+        Box::new(values.skip(1).step_by(3))
+    } else {
+        Box::new(values)
+    };
+    times.iter().copied().zip(values).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image(format: gltf::image::Format, channels: usize, width: u32) -> gltf::image::Data {
+        gltf::image::Data {
+            pixels: (0..channels * width as usize * 3)
+                .map(|i| i as u8)
+                .collect(),
+            format,
+            width,
+            height: 3,
+        }
+    }
+
+    #[test]
+    fn odd_width_rgb_images_stay_tightly_packed() {
+        let converted = convert_image(image(gltf::image::Format::R8G8B8, 3, 3));
+        assert!(matches!(converted.format(), TextureFormat::RGB));
+        assert_eq!(converted.size(), (3, 3));
+        assert_eq!(converted.data().len(), 27);
+        assert_eq!(
+            converted.data().len(),
+            opengl_sys::pixel_data_size(3, 3, TextureFormat::RGB, opengl_sys::DataType::U8)
+        );
+    }
+
+    #[test]
+    fn other_formats_expand_to_rgba() {
+        let converted = convert_image(image(gltf::image::Format::R8, 1, 3));
+        assert!(matches!(converted.format(), TextureFormat::RGBA));
+        assert_eq!(converted.data().len(), 36);
+        assert_eq!(converted.data()[4..8], [1, 1, 1, 255]);
+    }
+}
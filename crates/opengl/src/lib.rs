@@ -1,11 +1,36 @@
+mod animation;
+#[cfg(feature = "assimp")]
+mod assimp_import;
 mod buffer;
 mod error;
+mod frame_buffer;
+mod gltf_import;
+mod loader;
 mod mesh;
+mod mesh_processing;
+mod model;
+mod outline;
+mod picking;
+mod render_state;
+mod sampler;
+mod scene_graph;
 mod shader;
 mod texture;
+mod transparency;
 
+pub use animation::*;
 pub use buffer::*;
 pub use error::*;
+pub use frame_buffer::*;
+pub use loader::*;
 pub use mesh::*;
+pub use mesh_processing::*;
+pub use model::*;
+pub use outline::*;
+pub use picking::*;
+pub use render_state::*;
+pub use sampler::*;
+pub use scene_graph::*;
 pub use shader::*;
 pub use texture::*;
+pub use transparency::*;
@@ -0,0 +1,238 @@
+use crate::error::*;
+use std::sync::{mpsc, Arc, Mutex, PoisonError};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+type Job = Box<dyn FnOnce() + Send + 'static>;
+
+/// CPU-side asset data that still needs turning into GPU resources on the GL thread.
+pub trait Upload {
+    type Output;
+    fn upload(self) -> anyhow::Result<Self::Output>;
+}
+
+/// Pool of worker threads that decode assets away from the GL thread.
+pub struct AssetLoader {
+    jobs: Option<mpsc::Sender<Job>>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl AssetLoader {
+    pub fn new(num_workers: usize) -> Self {
+        let (jobs, receiver) = mpsc::channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+
+        let workers = (0..num_workers.max(1))
+            .map(|_| {
+                let receiver = Arc::clone(&receiver);
+                std::thread::spawn(move || loop {
+                    let job = receiver
+                        .lock()
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .recv();
+                    match job {
+                        Ok(job) => job(),
+                        Err(_) => break,
+                    }
+                })
+            })
+            .collect();
+
+        Self {
+            jobs: Some(jobs),
+            workers,
+        }
+    }
+
+    /// Runs `decode` on a worker, the result is uploaded later through [`Streaming::poll`]. A
+    /// panic in `decode` is reported as an error when polling rather than killing the worker.
+    pub fn load<Data, F>(&self, decode: F) -> Streamed<Data>
+    where
+        Data: Upload + Send + 'static,
+        F: FnOnce() -> anyhow::Result<Data> + Send + 'static,
+    {
+        let (sender, receiver) = mpsc::channel();
+        let job: Job = Box::new(move || {
+            let data = std::panic::catch_unwind(std::panic::AssertUnwindSafe(decode))
+                .unwrap_or_else(|panic| {
+                    Err(Error::AssetLoadPanicked(panic_message(&*panic)).into())
+                });
+            let _ = sender.send(data);
+        });
+        if let Some(jobs) = &self.jobs {
+            let _ = jobs.send(job);
+        }
+
+        Streamed {
+            state: StreamedState::Pending(receiver),
+        }
+    }
+}
+
+impl Drop for AssetLoader {
+    fn drop(&mut self) {
+        self.jobs = None;
+        for worker in self.workers.drain(..) {
+            // Jobs catch their own panics, and panicking here could abort during an unwind
+            let _ = worker.join();
+        }
+    }
+}
+
+fn panic_message(panic: &(dyn std::any::Any + Send)) -> String {
+    panic
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| panic.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string())
+}
+
+enum StreamedState<Data: Upload> {
+    Pending(mpsc::Receiver<anyhow::Result<Data>>),
+    Ready(Data::Output),
+    Failed,
+}
+
+/// An asset being decoded by an [`AssetLoader`], available once it has been uploaded.
+pub struct Streamed<Data: Upload> {
+    state: StreamedState<Data>,
+}
+
+impl<Data: Upload> Streamed<Data> {
+    /// An asset that was loaded synchronously.
+    pub fn ready(output: Data::Output) -> Self {
+        Self {
+            state: StreamedState::Ready(output),
+        }
+    }
+
+    pub fn get(&self) -> Option<&Data::Output> {
+        match &self.state {
+            StreamedState::Ready(output) => Some(output),
+            _ => None,
+        }
+    }
+
+    pub fn get_mut(&mut self) -> Option<&mut Data::Output> {
+        match &mut self.state {
+            StreamedState::Ready(output) => Some(output),
+            _ => None,
+        }
+    }
+}
+
+pub trait Streaming {
+    /// Whether polling could still make progress.
+    fn is_pending(&self) -> bool;
+
+    /// Uploads the decoded data if the worker has finished, returns whether an upload happened.
+    fn poll(&mut self) -> anyhow::Result<bool>;
+}
+
+impl<Data: Upload> Streaming for Streamed<Data> {
+    fn is_pending(&self) -> bool {
+        matches!(self.state, StreamedState::Pending(_))
+    }
+
+    fn poll(&mut self) -> anyhow::Result<bool> {
+        let StreamedState::Pending(receiver) = &self.state else {
+            return Ok(false);
+        };
+
+        let data = match receiver.try_recv() {
+            Ok(data) => data,
+            Err(mpsc::TryRecvError::Empty) => return Ok(false),
+            Err(mpsc::TryRecvError::Disconnected) => Err(Error::AssetLoadAborted.into()),
+        };
+
+        match data.and_then(Upload::upload) {
+            Ok(output) => {
+                self.state = StreamedState::Ready(output);
+                Ok(true)
+            }
+            Err(error) => {
+                self.state = StreamedState::Failed;
+                Err(error)
+            }
+        }
+    }
+}
+
+/// Uploads finished assets until `budget` has been spent. An asset that fails doesn't stop the
+/// others, returns how many are still pending along with the errors of those that failed.
+pub fn upload_ready(
+    budget: Duration,
+    assets: &mut [&mut dyn Streaming],
+) -> (usize, Vec<anyhow::Error>) {
+    let deadline = Instant::now() + budget;
+    let mut errors = Vec::new();
+    for asset in assets.iter_mut().filter(|asset| asset.is_pending()) {
+        if Instant::now() >= deadline {
+            break;
+        }
+        if let Err(error) = asset.poll() {
+            errors.push(error);
+        }
+    }
+
+    let pending = assets.iter().filter(|asset| asset.is_pending()).count();
+    (pending, errors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Number(u32);
+
+    impl Upload for Number {
+        type Output = u32;
+
+        fn upload(self) -> anyhow::Result<u32> {
+            Ok(self.0)
+        }
+    }
+
+    /// Uploads until nothing is pending, collecting every error along the way.
+    fn upload_all(assets: &mut [&mut dyn Streaming]) -> Vec<anyhow::Error> {
+        let mut errors = Vec::new();
+        for _ in 0..1000 {
+            let (pending, new_errors) = upload_ready(Duration::from_secs(1), assets);
+            errors.extend(new_errors);
+            if pending == 0 {
+                return errors;
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        panic!("assets still pending");
+    }
+
+    #[test]
+    fn failures_dont_stop_other_uploads() {
+        let loader = AssetLoader::new(2);
+        let mut failed = loader.load(|| -> anyhow::Result<Number> { anyhow::bail!("missing") });
+        let mut loaded = loader.load(|| Ok(Number(7)));
+
+        let errors = upload_all(&mut [&mut failed, &mut loaded]);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(failed.get(), None);
+        assert_eq!(loaded.get(), Some(&7));
+    }
+
+    #[test]
+    fn panics_are_reported_as_errors() {
+        let loader = AssetLoader::new(1);
+        let mut panicked = loader.load(|| -> anyhow::Result<Number> { panic!("corrupt file") });
+        let mut loaded = loader.load(|| Ok(Number(3)));
+
+        let errors = upload_all(&mut [&mut panicked, &mut loaded]);
+        assert_eq!(errors.len(), 1);
+        assert!(
+            errors[0].to_string().contains("corrupt file"),
+            "{}",
+            errors[0]
+        );
+        // The worker survived to decode the next asset
+        assert_eq!(loaded.get(), Some(&3));
+    }
+}
@@ -3,12 +3,52 @@ pub use opengl_sys::{DataType, DrawMode};
 use crate::buffer::*;
 use opengl_sys::BufferTarget;
 
+/// Axis-aligned box around a mesh's vertex positions, in model space.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Bounds {
+    pub min: [f32; 3],
+    pub max: [f32; 3],
+}
+
+impl Bounds {
+    /// `None` when there are no points.
+    pub fn from_points<I: IntoIterator<Item = [f32; 3]>>(points: I) -> Option<Self> {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+        Some(points.fold(
+            Bounds {
+                min: first,
+                max: first,
+            },
+            |bounds, point| Bounds {
+                min: std::array::from_fn(|i| bounds.min[i].min(point[i])),
+                max: std::array::from_fn(|i| bounds.max[i].max(point[i])),
+            },
+        ))
+    }
+
+    pub fn center(&self) -> [f32; 3] {
+        std::array::from_fn(|i| (self.min[i] + self.max[i]) / 2.0)
+    }
+
+    /// Radius of the sphere around [`Bounds::center`] that contains the box.
+    pub fn radius(&self) -> f32 {
+        (0..3)
+            .map(|i| (self.max[i] - self.min[i]) / 2.0)
+            .map(|half_extent| half_extent * half_extent)
+            .sum::<f32>()
+            .sqrt()
+    }
+}
+
 #[derive(Clone)]
 pub struct Mesh {
     vertex_array: VertexArray,
     vertex_buffer: Buffer,
     index_buffer: Option<Buffer>,
     instance_buffer: Option<Buffer>,
+    instance_layout: Vec<BufferAttribute>,
+    bounds: Option<Bounds>,
 }
 
 impl<const N: usize> TryFrom<&[[f32; N]]> for Mesh {
@@ -23,11 +63,17 @@ impl<const N: usize> TryFrom<&[[f32; N]]> for Mesh {
         let indices: Option<&[()]> = None;
         let instances: Option<(&[()], &[BufferAttribute])> = None;
 
-        Mesh::new(vertices, &[(0, attribute_size).into()], indices, instances)
+        let mut mesh = Mesh::new(vertices, &[(0, attribute_size).into()], indices, instances)?;
+        mesh.set_bounds(Bounds::from_points(vertices.iter().map(|vertex| {
+            std::array::from_fn(|i| vertex.get(i).copied().unwrap_or_default())
+        })));
+        Ok(mesh)
     }
 }
 
 impl Mesh {
+    /// The vertex layout is opaque here, so the mesh has no bounds until they're given with
+    /// [`Mesh::set_bounds`].
     pub fn new<Vertex, Index, Instance>(
         vertices: &[Vertex],
         vertex_layout: &[BufferAttribute],
@@ -48,12 +94,12 @@ impl Mesh {
             None
         };
 
-        let instance_buffer = if let Some((instances, layout)) = instances {
+        let (instance_buffer, instance_layout) = if let Some((instances, layout)) = instances {
             let mut instance_buffer = Buffer::new(BufferTarget::Array);
             instance_buffer.bind(instances, layout)?;
-            Some(instance_buffer)
+            (Some(instance_buffer), layout.to_vec())
         } else {
-            None
+            (None, Vec::new())
         };
 
         opengl_sys::bind_vertex_array(0)?;
@@ -63,9 +109,34 @@ impl Mesh {
             vertex_buffer,
             index_buffer,
             instance_buffer,
+            instance_layout,
+            bounds: None,
         })
     }
 
+    /// `None` when the mesh was created without knowing where its positions are.
+    pub fn bounds(&self) -> Option<Bounds> {
+        self.bounds
+    }
+
+    /// Usually from [`Bounds::from_points`], or to account for a vertex shader displacing the
+    /// positions.
+    pub fn set_bounds(&mut self, bounds: Option<Bounds>) {
+        self.bounds = bounds;
+    }
+
+    /// Replaces the per-instance data, keeping the layout the mesh was created with. Useful
+    /// for drawing only the instances that survived culling.
+    pub fn set_instances<Instance>(&mut self, instances: &[Instance]) -> anyhow::Result<()> {
+        let Some(instance_buffer) = &mut self.instance_buffer else {
+            anyhow::bail!("Mesh was created without instances");
+        };
+        self.vertex_array.bind()?;
+        instance_buffer.bind(instances, &self.instance_layout)?;
+        opengl_sys::bind_vertex_array(0)?;
+        Ok(())
+    }
+
     pub fn draw(&self, draw_mode: DrawMode) -> anyhow::Result<()> {
         self.vertex_array.bind()?;
 
@@ -0,0 +1,595 @@
+use crate::error::*;
+use crate::model::*;
+use nalgebra_glm as glm;
+use std::collections::HashMap;
+
+/// Vertices kept by the cache model [`MeshData::optimise_vertex_cache`] optimises for.
+const VERTEX_CACHE_SIZE: usize = 32;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum NormalMode {
+    /// Averaged over every triangle touching a position, weighted by area.
+    Smooth,
+    /// One normal per triangle, which unwelds the mesh.
+    Flat,
+}
+
+/// Processing expects [`MeshData::check_indices`] to have passed and may panic otherwise.
+impl MeshData {
+    /// Drops a trailing partial triangle and checks every index refers to a vertex.
+    pub fn check_indices(&mut self) -> anyhow::Result<()> {
+        self.indices.truncate(self.indices.len() / 3 * 3);
+        let vertex_count = self.vertices.len();
+        match self
+            .indices
+            .iter()
+            .find(|&&index| index as usize >= vertex_count)
+        {
+            Some(&index) => Err(Error::InvalidVertexIndex(index, vertex_count).into()),
+            None => Ok(()),
+        }
+    }
+
+    /// Gives every triangle corner its own vertex.
+    pub fn unweld(&mut self) {
+        let sources = std::mem::take(&mut self.indices);
+        self.indices = (0..sources.len() as u32).collect();
+        self.select_vertices(&sources);
+    }
+
+    /// Merges vertices whose attributes all match to within `tolerance`. Values are snapped to
+    /// a grid of that size, so a `tolerance` of 0 only merges exact duplicates.
+    pub fn weld(&mut self, tolerance: f32) {
+        let mut unique: HashMap<Vec<i64>, u32> = HashMap::with_capacity(self.vertices.len());
+        let mut sources = Vec::new();
+        let remap: Vec<u32> = (0..self.vertices.len())
+            .map(|vertex| {
+                let key = self
+                    .attributes(vertex)
+                    .map(|value| {
+                        if tolerance > 0.0 {
+                            (value / tolerance).round() as i64
+                        } else {
+                            value.to_bits() as i64
+                        }
+                    })
+                    .collect();
+                *unique.entry(key).or_insert_with(|| {
+                    sources.push(vertex as u32);
+                    sources.len() as u32 - 1
+                })
+            })
+            .collect();
+
+        for index in &mut self.indices {
+            *index = remap[*index as usize];
+        }
+        self.select_vertices(&sources);
+    }
+
+    /// Replaces the normals of every vertex, existing tangents are dropped as they no longer
+    /// match.
+    pub fn recompute_normals(&mut self, mode: NormalMode) {
+        if mode == NormalMode::Flat {
+            self.unweld();
+        }
+
+        let position = |vertex: u32| -> glm::Vec3 {
+            let vertex = &self.vertices[vertex as usize];
+            glm::vec3(vertex[0], vertex[1], vertex[2])
+        };
+        let key = |position: glm::Vec3| [position.x, position.y, position.z].map(f32::to_bits);
+        let mut normals = vec![glm::Vec3::zeros(); self.vertices.len()];
+        let mut by_position: HashMap<[u32; 3], glm::Vec3> = HashMap::new();
+        for triangle in self.indices.chunks_exact(3) {
+            let [a, b, c] = [0, 1, 2].map(|corner| position(triangle[corner]));
+            // Not normalised, so larger triangles count for more
+            let normal = glm::cross(&(b - a), &(c - a));
+            for &vertex in triangle {
+                match mode {
+                    NormalMode::Flat => normals[vertex as usize] = normal,
+                    NormalMode::Smooth => {
+                        *by_position.entry(key(position(vertex))).or_default() += normal;
+                    }
+                }
+            }
+        }
+        if mode == NormalMode::Smooth {
+            for (vertex, normal) in normals.iter_mut().enumerate() {
+                let sum = by_position.get(&key(position(vertex as u32)));
+                *normal = sum.copied().unwrap_or_default();
+            }
+        }
+
+        for (vertex, normal) in self.vertices.iter_mut().zip(normals) {
+            // Degenerate triangles point up rather than giving NaN
+            let normal = if normal.norm() > f32::EPSILON {
+                normal.normalize()
+            } else {
+                glm::Vec3::y()
+            };
+            vertex[3..6].copy_from_slice(normal.as_slice());
+        }
+        self.tangents = None;
+    }
+
+    /// Generates MikkTSpace tangents from the normals and the given texture coordinate set,
+    /// which is what normal maps baked by most tools expect. Vertices are split wherever
+    /// triangles sharing them need different tangents.
+    pub fn generate_tangents(&mut self, uv_set: u32) -> anyhow::Result<()> {
+        let mut geometry = TangentGeometry {
+            mesh: self,
+            uv_set,
+            tangents: vec![[0.0; 4]; self.indices.len() / 3 * 3],
+        };
+        if !bevy_mikktspace::generate_tangents(&mut geometry) {
+            return Err(Error::TangentGeneration.into());
+        }
+        let tangents = geometry.tangents;
+
+        self.indices.truncate(tangents.len());
+        self.unweld();
+        self.tangents = Some(tangents);
+        self.weld(0.0);
+        Ok(())
+    }
+
+    /// Reorders the triangles so recently used vertices are reused while still in the
+    /// post-transform cache, using Tom Forsyth's linear-speed algorithm, then reorders the
+    /// vertices into the order they are first used.
+    pub fn optimise_vertex_cache(&mut self) {
+        let triangle_count = self.indices.len() / 3;
+        let vertex_count = self.vertices.len();
+
+        let mut vertex_triangles: Vec<Vec<usize>> = vec![Vec::new(); vertex_count];
+        for (triangle, corners) in self.indices.chunks_exact(3).enumerate() {
+            for &vertex in corners {
+                vertex_triangles[vertex as usize].push(triangle);
+            }
+        }
+        let mut remaining: Vec<usize> = vertex_triangles.iter().map(Vec::len).collect();
+        let mut vertex_scores: Vec<f32> = remaining
+            .iter()
+            .map(|&remaining| vertex_score(None, remaining))
+            .collect();
+        let triangle_score = |triangle: usize, vertex_scores: &[f32]| -> f32 {
+            self.indices[3 * triangle..3 * triangle + 3]
+                .iter()
+                .map(|&vertex| vertex_scores[vertex as usize])
+                .sum()
+        };
+        let mut triangle_scores: Vec<f32> = (0..triangle_count)
+            .map(|triangle| triangle_score(triangle, &vertex_scores))
+            .collect();
+
+        let mut emitted = vec![false; triangle_count];
+        let mut cache: Vec<u32> = Vec::with_capacity(VERTEX_CACHE_SIZE + 3);
+        let mut order = Vec::with_capacity(self.indices.len());
+        let mut next_unemitted = 0;
+        let mut best = None;
+        for _ in 0..triangle_count {
+            // Without a candidate from the cache, carry on from the first triangle not drawn
+            let triangle = match best.take() {
+                Some(triangle) => triangle,
+                None => {
+                    while emitted[next_unemitted] {
+                        next_unemitted += 1;
+                    }
+                    next_unemitted
+                }
+            };
+            emitted[triangle] = true;
+
+            let corners = &self.indices[3 * triangle..3 * triangle + 3];
+            order.extend_from_slice(corners);
+            for &vertex in corners {
+                remaining[vertex as usize] -= 1;
+                cache.retain(|&cached| cached != vertex);
+            }
+            cache.splice(0..0, corners.iter().copied());
+
+            for (position, &vertex) in cache.iter().enumerate() {
+                let position = (position < VERTEX_CACHE_SIZE).then_some(position);
+                vertex_scores[vertex as usize] = vertex_score(position, remaining[vertex as usize]);
+            }
+            let mut best_score = f32::MIN;
+            for &vertex in &cache {
+                for &triangle in &vertex_triangles[vertex as usize] {
+                    if emitted[triangle] {
+                        continue;
+                    }
+                    triangle_scores[triangle] = triangle_score(triangle, &vertex_scores);
+                    if triangle_scores[triangle] > best_score {
+                        best_score = triangle_scores[triangle];
+                        best = Some(triangle);
+                    }
+                }
+            }
+            cache.truncate(VERTEX_CACHE_SIZE);
+        }
+
+        // Vertices in order of first use, unused ones are dropped
+        let mut remap = vec![u32::MAX; vertex_count];
+        let mut sources = Vec::with_capacity(vertex_count);
+        for index in &mut order {
+            let new_index = &mut remap[*index as usize];
+            if *new_index == u32::MAX {
+                *new_index = sources.len() as u32;
+                sources.push(*index);
+            }
+            *index = *new_index;
+        }
+        self.indices = order;
+        self.select_vertices(&sources);
+    }
+
+    /// Rebuilds every vertex stream from the vertices at `sources`.
+    fn select_vertices(&mut self, sources: &[u32]) {
+        self.vertices = select(&self.vertices, sources);
+        self.skin = self.skin.as_deref().map(|skin| select(skin, sources));
+        self.tangents = self
+            .tangents
+            .as_deref()
+            .map(|tangents| select(tangents, sources));
+        self.second_uvs = self.second_uvs.as_deref().map(|uvs| select(uvs, sources));
+    }
+
+    fn attributes(&self, vertex: usize) -> impl Iterator<Item = f32> + '_ {
+        let skin = self.skin.iter().filter_map(move |skin| skin.get(vertex));
+        let tangents = self
+            .tangents
+            .iter()
+            .filter_map(move |tangents| tangents.get(vertex));
+        let second_uvs = self
+            .second_uvs
+            .iter()
+            .filter_map(move |uvs| uvs.get(vertex));
+        self.vertices[vertex]
+            .iter()
+            .chain(skin.flatten())
+            .chain(tangents.flatten())
+            .chain(second_uvs.flatten())
+            .copied()
+    }
+}
+
+impl SceneData {
+    /// Generates tangents for normal mapped meshes that don't have them and optimises every
+    /// mesh for the vertex cache.
+    pub fn process_meshes(&mut self) -> anyhow::Result<()> {
+        for mesh in &mut self.meshes {
+            let normal_map = self
+                .materials
+                .get(mesh.material_index)
+                .and_then(|material| material.normal);
+            if let Some(normal_map) = normal_map {
+                if mesh.tangents.is_none() {
+                    mesh.generate_tangents(normal_map.uv_set)?;
+                }
+            }
+            mesh.optimise_vertex_cache();
+        }
+        Ok(())
+    }
+}
+
+/// Streams shorter than the vertex data are padded with zeros.
+fn select<T: Copy + Default>(stream: &[T], sources: &[u32]) -> Vec<T> {
+    sources
+        .iter()
+        .map(|&source| stream.get(source as usize).copied().unwrap_or_default())
+        .collect()
+}
+
+/// Favours the vertices used most recently and those with the fewest triangles left to draw.
+fn vertex_score(cache_position: Option<usize>, remaining: usize) -> f32 {
+    if remaining == 0 {
+        return -1.0;
+    }
+    let cache_score = match cache_position {
+        // The last triangle's vertices score lower so it isn't simply repeated
+        Some(position) if position < 3 => 0.75,
+        Some(position) => {
+            let scale = 1.0 / (VERTEX_CACHE_SIZE - 3) as f32;
+            (1.0 - (position - 3) as f32 * scale).powf(1.5)
+        }
+        None => 0.0,
+    };
+    cache_score + 2.0 / (remaining as f32).sqrt()
+}
+
+struct TangentGeometry<'a> {
+    mesh: &'a MeshData,
+    uv_set: u32,
+    /// One per triangle corner.
+    tangents: Vec<[f32; 4]>,
+}
+
+impl TangentGeometry<'_> {
+    fn vertex(&self, face: usize, vert: usize) -> usize {
+        self.mesh.indices[3 * face + vert] as usize
+    }
+}
+
+impl bevy_mikktspace::Geometry for TangentGeometry<'_> {
+    fn num_faces(&self) -> usize {
+        self.tangents.len() / 3
+    }
+
+    fn num_vertices_of_face(&self, _face: usize) -> usize {
+        3
+    }
+
+    fn position(&self, face: usize, vert: usize) -> [f32; 3] {
+        let vertex = &self.mesh.vertices[self.vertex(face, vert)];
+        [vertex[0], vertex[1], vertex[2]]
+    }
+
+    fn normal(&self, face: usize, vert: usize) -> [f32; 3] {
+        let vertex = &self.mesh.vertices[self.vertex(face, vert)];
+        [vertex[3], vertex[4], vertex[5]]
+    }
+
+    fn tex_coord(&self, face: usize, vert: usize) -> [f32; 2] {
+        let vertex = self.vertex(face, vert);
+        match (self.uv_set, &self.mesh.second_uvs) {
+            (1, Some(uvs)) => uvs.get(vertex).copied().unwrap_or_default(),
+            _ => [self.mesh.vertices[vertex][6], self.mesh.vertices[vertex][7]],
+        }
+    }
+
+    fn set_tangent_encoded(&mut self, tangent: [f32; 4], face: usize, vert: usize) {
+        self.tangents[3 * face + vert] = tangent;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vertex(position: [f32; 3], uv: [f32; 2]) -> [f32; 8] {
+        let [x, y, z] = position;
+        let [u, v] = uv;
+        [x, y, z, 0.0, 0.0, 1.0, u, v]
+    }
+
+    fn mesh(vertices: Vec<[f32; 8]>, indices: Vec<u32>) -> MeshData {
+        MeshData {
+            vertices,
+            indices,
+            material_index: 0,
+            skin: None,
+            tangents: None,
+            second_uvs: None,
+        }
+    }
+
+    /// A unit square in the xy plane with every corner of both triangles duplicated.
+    fn unwelded_quad() -> MeshData {
+        let corners = [
+            [0.0, 0.0],
+            [1.0, 0.0],
+            [1.0, 1.0],
+            [0.0, 0.0],
+            [1.0, 1.0],
+            [0.0, 1.0],
+        ];
+        let vertices = corners
+            .iter()
+            .map(|&[x, y]| vertex([x, y, 0.0], [x, y]))
+            .collect();
+        mesh(vertices, (0..6).collect())
+    }
+
+    /// `size` by `size` quads, with the triangles in a scattered order.
+    fn grid(size: u32) -> MeshData {
+        let vertices = (0..=size)
+            .flat_map(|y| (0..=size).map(move |x| [x as f32, y as f32]))
+            .map(|[x, y]| vertex([x, y, 0.0], [x, y]))
+            .collect();
+        let mut triangles = Vec::new();
+        for y in 0..size {
+            for x in 0..size {
+                let corner = y * (size + 1) + x;
+                let above = corner + size + 1;
+                triangles.push([corner, corner + 1, above + 1]);
+                triangles.push([corner, above + 1, above]);
+            }
+        }
+        let count = triangles.len();
+        let indices = (0..count)
+            .flat_map(|i| triangles[i * 7919 % count])
+            .collect();
+        mesh(vertices, indices)
+    }
+
+    /// Each triangle as its positions, rotated to start at the smallest so winding is kept.
+    fn triangles(mesh: &MeshData) -> Vec<[[u32; 3]; 3]> {
+        let mut triangles: Vec<_> = mesh
+            .indices
+            .chunks_exact(3)
+            .map(|triangle| {
+                let mut corners = [0, 1, 2].map(|corner| {
+                    let vertex = &mesh.vertices[triangle[corner] as usize];
+                    [vertex[0], vertex[1], vertex[2]].map(f32::to_bits)
+                });
+                let first = (0..3).min_by_key(|&corner| corners[corner]).unwrap();
+                corners.rotate_left(first);
+                corners
+            })
+            .collect();
+        triangles.sort();
+        triangles
+    }
+
+    /// Cache misses per triangle with a FIFO post-transform cache.
+    fn average_cache_miss_ratio(mesh: &MeshData, cache_size: usize) -> f32 {
+        let mut cache = std::collections::VecDeque::new();
+        let mut misses = 0;
+        for &index in &mesh.indices {
+            if !cache.contains(&index) {
+                misses += 1;
+                cache.push_back(index);
+                if cache.len() > cache_size {
+                    cache.pop_front();
+                }
+            }
+        }
+        misses as f32 / (mesh.indices.len() / 3) as f32
+    }
+
+    #[test]
+    fn check_indices_drops_partial_triangles() {
+        let mut quad = unwelded_quad();
+        quad.indices.extend([0, 1]);
+        quad.check_indices().unwrap();
+        assert_eq!(quad.indices.len(), 6);
+
+        quad.indices[4] = 6;
+        assert!(quad.check_indices().is_err());
+    }
+
+    #[test]
+    fn weld_merges_exact_duplicates() {
+        let mut quad = unwelded_quad();
+        let before = triangles(&quad);
+        quad.weld(0.0);
+        assert_eq!(quad.vertices.len(), 4);
+        assert_eq!(triangles(&quad), before);
+    }
+
+    #[test]
+    fn weld_keeps_vertices_with_different_attributes() {
+        let mut quad = unwelded_quad();
+        quad.vertices[3][6] = 0.5;
+        quad.weld(0.0);
+        assert_eq!(quad.vertices.len(), 5);
+    }
+
+    #[test]
+    fn weld_within_tolerance() {
+        let mut quad = unwelded_quad();
+        quad.vertices[3][0] = 1e-4;
+        quad.weld(0.0);
+        assert_eq!(quad.vertices.len(), 5);
+        quad.weld(1e-2);
+        assert_eq!(quad.vertices.len(), 4);
+    }
+
+    #[test]
+    fn weld_compares_every_vertex_stream() {
+        let mut quad = unwelded_quad();
+        let mut second_uvs = vec![[0.0; 2]; 6];
+        second_uvs[3] = [1.0, 1.0];
+        quad.second_uvs = Some(second_uvs);
+        quad.weld(0.0);
+        assert_eq!(quad.vertices.len(), 5);
+        assert_eq!(quad.second_uvs.as_ref().map(Vec::len), Some(5));
+    }
+
+    #[test]
+    fn smooth_normals_are_shared_across_split_vertices() {
+        // Two triangles folded along the x axis, with the shared edge's vertices split
+        let mut folded = mesh(
+            vec![
+                vertex([0.0, 0.0, 0.0], [0.0, 0.0]),
+                vertex([1.0, 0.0, 0.0], [1.0, 0.0]),
+                vertex([0.0, 1.0, 0.0], [0.0, 1.0]),
+                vertex([0.0, 0.0, 0.0], [0.5, 0.5]),
+                vertex([0.0, 0.0, -1.0], [0.0, 1.0]),
+                vertex([1.0, 0.0, 0.0], [0.5, 0.5]),
+            ],
+            vec![0, 1, 2, 3, 5, 4],
+        );
+        folded.recompute_normals(NormalMode::Smooth);
+        let normal = |vertex: usize| glm::make_vec3(&folded.vertices[vertex][3..6]);
+        let expected = glm::normalize(&glm::vec3(0.0, 1.0, 1.0));
+        for vertex in [0, 1, 3, 5] {
+            assert!(glm::distance(&normal(vertex), &expected) < 1e-5);
+        }
+        assert!(glm::distance(&normal(2), &glm::Vec3::z()) < 1e-5);
+        assert!(glm::distance(&normal(4), &glm::Vec3::y()) < 1e-5);
+    }
+
+    #[test]
+    fn flat_normals_unweld() {
+        let mut quad = unwelded_quad();
+        quad.weld(0.0);
+        quad.tangents = Some(vec![[1.0, 0.0, 0.0, 1.0]; 4]);
+        quad.recompute_normals(NormalMode::Flat);
+        assert_eq!(quad.vertices.len(), 6);
+        assert!(quad.tangents.is_none());
+        for vertex in &quad.vertices {
+            assert_eq!(vertex[3..6], [0.0, 0.0, 1.0]);
+        }
+    }
+
+    #[test]
+    fn degenerate_triangles_point_up() {
+        let mut line = mesh(
+            vec![
+                vertex([0.0; 3], [0.0; 2]),
+                vertex([1.0, 0.0, 0.0], [0.0; 2]),
+                vertex([2.0, 0.0, 0.0], [0.0; 2]),
+            ],
+            vec![0, 1, 2],
+        );
+        line.recompute_normals(NormalMode::Smooth);
+        for vertex in &line.vertices {
+            assert_eq!(vertex[3..6], [0.0, 1.0, 0.0]);
+        }
+    }
+
+    #[test]
+    fn tangents_follow_the_texture_u_direction() {
+        let mut quad = unwelded_quad();
+        quad.weld(0.0);
+        quad.generate_tangents(0).unwrap();
+        let tangents = quad.tangents.as_ref().unwrap();
+        assert_eq!(tangents.len(), quad.vertices.len());
+        for tangent in tangents {
+            assert!(glm::distance(&glm::make_vec3(&tangent[..3]), &glm::Vec3::x()) < 1e-4);
+            assert_eq!(tangent[3], 1.0);
+        }
+    }
+
+    #[test]
+    fn optimise_vertex_cache_keeps_the_triangles() {
+        let mut grid = grid(16);
+        let before = triangles(&grid);
+        grid.optimise_vertex_cache();
+        assert_eq!(triangles(&grid), before);
+        assert_eq!(grid.vertices.len(), 17 * 17);
+    }
+
+    #[test]
+    fn optimise_vertex_cache_orders_vertices_by_first_use() {
+        let mut grid = grid(8);
+        grid.optimise_vertex_cache();
+        let mut next = 0;
+        for &index in &grid.indices {
+            assert!(index <= next);
+            if index == next {
+                next += 1;
+            }
+        }
+    }
+
+    #[test]
+    fn optimise_vertex_cache_reduces_cache_misses() {
+        let mut grid = grid(32);
+        let before = average_cache_miss_ratio(&grid, 16);
+        grid.optimise_vertex_cache();
+        let after = average_cache_miss_ratio(&grid, 16);
+        assert!(after < 0.8, "{after}");
+        assert!(after < before / 2.0, "{before} -> {after}");
+    }
+
+    #[test]
+    fn optimise_vertex_cache_drops_unused_vertices() {
+        let mut quad = unwelded_quad();
+        quad.vertices.push(vertex([5.0; 3], [0.0; 2]));
+        quad.optimise_vertex_cache();
+        assert_eq!(quad.vertices.len(), 6);
+    }
+}
@@ -0,0 +1,604 @@
+use crate::animation::*;
+use crate::buffer::*;
+use crate::loader::*;
+use crate::mesh::*;
+use crate::sampler::*;
+use crate::scene_graph::*;
+use crate::shader::*;
+use crate::texture::*;
+use nalgebra_glm as glm;
+use std::path::{Path, PathBuf};
+
+/// Sampler names under `material.` in the order of [`Material::maps`], with the colour used
+/// in place of a missing or still loading map. The placeholders leave the factors unchanged,
+/// except for specular which gives no highlights.
+const TEXTURE_MAPS: [(&str, [u8; 4]); 6] = [
+    ("diffuse", [255, 255, 255, 255]),
+    ("specular", [0, 0, 0, 255]),
+    ("metallic_roughness", [255, 255, 255, 255]),
+    ("normal", [128, 128, 255, 255]),
+    ("occlusion", [255, 255, 255, 255]),
+    ("emissive", [255, 255, 255, 255]),
+];
+
+pub enum TextureSource {
+    File(PathBuf),
+    /// Already decoded, e.g. embedded in the model file.
+    Image(ImageData),
+}
+
+/// Texture coordinate transform as in `KHR_texture_transform`.
+#[derive(Copy, Clone, Debug)]
+pub struct TextureTransform {
+    pub offset: [f32; 2],
+    /// Radians counter-clockwise.
+    pub rotation: f32,
+    pub scale: [f32; 2],
+}
+
+impl Default for TextureTransform {
+    fn default() -> Self {
+        TextureTransform {
+            offset: [0.0, 0.0],
+            rotation: 0.0,
+            scale: [1.0, 1.0],
+        }
+    }
+}
+
+impl TextureTransform {
+    /// Applies the scale, then the rotation, then the offset.
+    pub fn matrix(&self) -> glm::Mat3 {
+        let (sin, cos) = self.rotation.sin_cos();
+        let [x, y] = self.offset;
+        let [scale_x, scale_y] = self.scale;
+        glm::Mat3::new(1.0, 0.0, x, 0.0, 1.0, y, 0.0, 0.0, 1.0)
+            * glm::Mat3::new(cos, sin, 0.0, -sin, cos, 0.0, 0.0, 0.0, 1.0)
+            * glm::Mat3::new(scale_x, 0.0, 0.0, 0.0, scale_y, 0.0, 0.0, 0.0, 1.0)
+    }
+}
+
+/// One of a material's maps, `texture` indexes the scene's textures.
+#[derive(Copy, Clone, Debug)]
+pub struct TextureMap {
+    pub texture: usize,
+    pub uv_set: u32,
+    pub transform: TextureTransform,
+}
+
+impl TextureMap {
+    pub fn new(texture: usize) -> Self {
+        TextureMap {
+            texture,
+            uv_set: 0,
+            transform: Default::default(),
+        }
+    }
+}
+
+/// Covers both Phong style diffuse/specular materials and glTF metallic-roughness ones. The
+/// factors default to the glTF defaults.
+#[derive(Clone, Debug)]
+pub struct Material {
+    /// Also the base colour map of metallic-roughness materials.
+    pub diffuse: Option<TextureMap>,
+    pub specular: Option<TextureMap>,
+    /// Roughness in the green channel, metalness in blue.
+    pub metallic_roughness: Option<TextureMap>,
+    pub normal: Option<TextureMap>,
+    pub occlusion: Option<TextureMap>,
+    pub emissive: Option<TextureMap>,
+    pub base_colour_factor: [f32; 4],
+    pub metallic_factor: f32,
+    pub roughness_factor: f32,
+    pub emissive_factor: [f32; 3],
+    pub normal_scale: f32,
+    pub occlusion_strength: f32,
+}
+
+impl Default for Material {
+    fn default() -> Self {
+        Material {
+            diffuse: None,
+            specular: None,
+            metallic_roughness: None,
+            normal: None,
+            occlusion: None,
+            emissive: None,
+            base_colour_factor: [1.0; 4],
+            metallic_factor: 1.0,
+            roughness_factor: 1.0,
+            emissive_factor: [0.0; 3],
+            normal_scale: 1.0,
+            occlusion_strength: 1.0,
+        }
+    }
+}
+
+impl Material {
+    pub fn maps(&self) -> [Option<TextureMap>; 6] {
+        [
+            self.diffuse,
+            self.specular,
+            self.metallic_roughness,
+            self.normal,
+            self.occlusion,
+            self.emissive,
+        ]
+    }
+}
+
+/// Uploaded with position, normal and the first texture coordinates at attributes 0 to 2, bone
+/// indices and weights at 3 and 4, tangents at 5 and the second texture coordinates at 6.
+pub struct MeshData {
+    /// Interleaved position, normal and first texture coordinates.
+    pub vertices: Vec<[f32; 8]>,
+    pub indices: Vec<u32>,
+    pub material_index: usize,
+    /// Per vertex bone indices followed by their weights, for meshes with bones.
+    pub skin: Option<Vec<[f32; 8]>>,
+    /// Tangent with the handedness of the bitangent in `w`.
+    pub tangents: Option<Vec<[f32; 4]>>,
+    pub second_uvs: Option<Vec<[f32; 2]>>,
+}
+
+/// Everything imported from a model file that can be gathered off the GL thread.
+pub struct SceneData {
+    pub textures: Vec<TextureSource>,
+    pub materials: Vec<Material>,
+    pub meshes: Vec<MeshData>,
+    pub graph: SceneGraph,
+    pub skeleton: Skeleton,
+    pub animations: Vec<AnimationClip>,
+}
+
+impl SceneData {
+    /// Uses the native glTF importer for `.gltf` and `.glb` files and, with the `assimp`
+    /// feature, assimp for the rest.
+    pub fn import<P: AsRef<Path>>(path: P) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let is_gltf = path.extension().is_some_and(|extension| {
+            extension.eq_ignore_ascii_case("gltf") || extension.eq_ignore_ascii_case("glb")
+        });
+        if is_gltf {
+            return Self::import_gltf(path);
+        }
+
+        #[cfg(feature = "assimp")]
+        return Self::import_assimp(path);
+        #[cfg(not(feature = "assimp"))]
+        Err(crate::error::Error::UnsupportedModelFormat(path.to_path_buf()).into())
+    }
+}
+
+pub struct SceneMesh {
+    pub mesh: Mesh,
+    pub material_index: usize,
+    /// Bone indices and weights are at attributes 3 and 4.
+    pub skinned: bool,
+    /// Kept on the CPU for ray tests, in the bind pose for skinned meshes.
+    pub positions: Vec<[f32; 3]>,
+    pub indices: Vec<u32>,
+}
+
+/// The closest mesh a ray hit in a [`Scene`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SceneHit {
+    pub distance: f32,
+    pub node: NodeId,
+    /// Index into [`Scene::meshes`].
+    pub mesh: usize,
+    /// Index of the triangle within the mesh.
+    pub triangle: usize,
+}
+
+/// Meshes are kept in file order, so the node graph can draw the same mesh several times.
+pub struct Scene {
+    pub meshes: Vec<SceneMesh>,
+    pub materials: Vec<Material>,
+    pub graph: SceneGraph,
+    pub skeleton: Skeleton,
+    pub animations: Vec<AnimationClip>,
+    /// Handed over to the [`Model`] to load.
+    texture_sources: Vec<TextureSource>,
+}
+
+impl Scene {
+    /// Tests every mesh the graph draws with the model matrix [`Model::draw`] would use.
+    /// `intersect` returns the distance and triangle of the closest hit on one mesh, e.g. by
+    /// moving a `camera::Ray` into model space with the inverse model matrix and calling
+    /// `intersect_triangles` on the mesh's positions and indices.
+    pub fn intersect_ray<F>(&self, transform: &glm::Mat4, mut intersect: F) -> Option<SceneHit>
+    where
+        F: FnMut(&glm::Mat4, &SceneMesh) -> Option<(f32, usize)>,
+    {
+        let mut closest: Option<SceneHit> = None;
+        for (node, graph_node) in self.graph.nodes().iter().enumerate() {
+            let node_model = transform * graph_node.world_transform();
+            for &mesh in &graph_node.meshes {
+                let Some(scene_mesh) = self.meshes.get(mesh) else {
+                    continue;
+                };
+                // Skinned meshes are drawn in model space with the bones placing them
+                let model = if scene_mesh.skinned {
+                    transform
+                } else {
+                    &node_model
+                };
+                let Some((distance, triangle)) = intersect(model, scene_mesh) else {
+                    continue;
+                };
+                if closest.is_none_or(|closest| distance < closest.distance) {
+                    closest = Some(SceneHit {
+                        distance,
+                        node,
+                        mesh,
+                        triangle,
+                    });
+                }
+            }
+        }
+        closest
+    }
+}
+
+impl Upload for SceneData {
+    type Output = Scene;
+
+    fn upload(self) -> anyhow::Result<Scene> {
+        let meshes = self
+            .meshes
+            .iter()
+            .map(|mesh| {
+                Ok(SceneMesh {
+                    mesh: upload_mesh(mesh)?,
+                    material_index: mesh.material_index,
+                    skinned: mesh.skin.is_some(),
+                    positions: mesh
+                        .vertices
+                        .iter()
+                        .map(|vertex| [vertex[0], vertex[1], vertex[2]])
+                        .collect(),
+                    indices: mesh.indices.clone(),
+                })
+            })
+            .collect::<anyhow::Result<_>>()?;
+
+        Ok(Scene {
+            meshes,
+            materials: self.materials,
+            graph: self.graph,
+            skeleton: self.skeleton,
+            animations: self.animations,
+            texture_sources: self.textures,
+        })
+    }
+}
+
+/// Interleaves whichever optional attributes the mesh has after the ones every mesh has.
+fn upload_mesh(mesh: &MeshData) -> anyhow::Result<Mesh> {
+    let mut layout: Vec<BufferAttribute> = vec![
+        (0, BufferAttributeSize::Triple).into(),
+        (1, BufferAttributeSize::Triple).into(),
+        (2, BufferAttributeSize::Double).into(),
+    ];
+    let mut streams: Vec<(&[f32], usize)> = vec![(mesh.vertices.as_flattened(), 8)];
+    if let Some(skin) = &mesh.skin {
+        layout.push((3, BufferAttributeSize::Quadruple).into());
+        layout.push((4, BufferAttributeSize::Quadruple).into());
+        streams.push((skin.as_flattened(), 8));
+    }
+    if let Some(tangents) = &mesh.tangents {
+        layout.push((5, BufferAttributeSize::Quadruple).into());
+        streams.push((tangents.as_flattened(), 4));
+    }
+    if let Some(second_uvs) = &mesh.second_uvs {
+        layout.push((6, BufferAttributeSize::Double).into());
+        streams.push((second_uvs.as_flattened(), 2));
+    }
+
+    let stride: usize = streams.iter().map(|(_, components)| components).sum();
+    let mut vertices = Vec::with_capacity(stride * mesh.vertices.len());
+    for vertex in 0..mesh.vertices.len() {
+        for (stream, components) in &streams {
+            let start = vertex * components;
+            match stream.get(start..start + components) {
+                Some(values) => vertices.extend_from_slice(values),
+                None => vertices.extend(std::iter::repeat_n(0.0, *components)),
+            }
+        }
+    }
+
+    let mut gpu_mesh = Mesh::new(
+        &vertices,
+        &layout,
+        Some(&mesh.indices),
+        None as Option<(&[()], &[BufferAttribute])>,
+    )?;
+    gpu_mesh.set_bounds(Bounds::from_points(
+        mesh.vertices
+            .iter()
+            .map(|vertex| [vertex[0], vertex[1], vertex[2]]),
+    ));
+    Ok(gpu_mesh)
+}
+
+/// A model whose meshes and textures may still be streaming in. Missing maps are drawn with
+/// placeholders that leave the material's factors unchanged.
+pub struct Model {
+    scene: Streamed<SceneData>,
+    textures: Vec<Streamed<ImageData>>,
+    placeholders: [TextureImage2D; 6],
+}
+
+fn placeholders() -> anyhow::Result<[TextureImage2D; 6]> {
+    let [a, b, c, d, e, f] = TEXTURE_MAPS.map(|(_, colour)| TextureImage2D::solid_colour(colour));
+    Ok([a?, b?, c?, d?, e?, f?])
+}
+
+/// The texture unit and uniform locations of a material map, each is optional so shaders only
+/// declare what they use.
+struct MapUniforms {
+    texture_unit: Option<u32>,
+    transform: Option<UniformLocation>,
+    uv_set: Option<UniformLocation>,
+}
+
+/// Locations of the material factor uniforms.
+struct MaterialUniforms {
+    maps: Vec<MapUniforms>,
+    base_colour_factor: Option<UniformLocation>,
+    metallic_factor: Option<UniformLocation>,
+    roughness_factor: Option<UniformLocation>,
+    emissive_factor: Option<UniformLocation>,
+    normal_scale: Option<UniformLocation>,
+    occlusion_strength: Option<UniformLocation>,
+}
+
+impl MaterialUniforms {
+    fn find(shader: &ShaderProgram) -> anyhow::Result<Self> {
+        let maps = TEXTURE_MAPS
+            .iter()
+            .map(|(name, _)| {
+                let sampler = format!("material.{name}");
+                Ok(MapUniforms {
+                    transform: shader.find_uniform(&format!("{sampler}_transform"))?,
+                    uv_set: shader.find_uniform(&format!("{sampler}_uv_set"))?,
+                    texture_unit: shader.texture_unit(&sampler).ok(),
+                })
+            })
+            .collect::<anyhow::Result<_>>()?;
+
+        Ok(MaterialUniforms {
+            maps,
+            base_colour_factor: shader.find_uniform("material.base_colour_factor")?,
+            metallic_factor: shader.find_uniform("material.metallic_factor")?,
+            roughness_factor: shader.find_uniform("material.roughness_factor")?,
+            emissive_factor: shader.find_uniform("material.emissive_factor")?,
+            normal_scale: shader.find_uniform("material.normal_scale")?,
+            occlusion_strength: shader.find_uniform("material.occlusion_strength")?,
+        })
+    }
+
+    fn set_factors(&self, material: &Material) -> anyhow::Result<()> {
+        if let Some(location) = self.base_colour_factor {
+            opengl_sys::set_uniform_vec4(location, &material.base_colour_factor)?;
+        }
+        if let Some(location) = self.metallic_factor {
+            opengl_sys::set_uniform_f32(location, material.metallic_factor)?;
+        }
+        if let Some(location) = self.roughness_factor {
+            opengl_sys::set_uniform_f32(location, material.roughness_factor)?;
+        }
+        if let Some(location) = self.emissive_factor {
+            opengl_sys::set_uniform_vec3(location, &material.emissive_factor)?;
+        }
+        if let Some(location) = self.normal_scale {
+            opengl_sys::set_uniform_f32(location, material.normal_scale)?;
+        }
+        if let Some(location) = self.occlusion_strength {
+            opengl_sys::set_uniform_f32(location, material.occlusion_strength)?;
+        }
+        Ok(())
+    }
+}
+
+/// The uniforms [`Model::draw`] sets, looked up once for a program rather than on every draw.
+pub struct ModelUniforms {
+    model: UniformLocation,
+    material: MaterialUniforms,
+    /// `bones` and `skinned`, when the program declares both.
+    skinning: Option<(UniformLocation, UniformLocation)>,
+}
+
+impl ModelUniforms {
+    pub fn new(shader: &ShaderProgram) -> anyhow::Result<Self> {
+        let skinning = match (
+            shader.find_uniform("bones")?,
+            shader.find_uniform("skinned")?,
+        ) {
+            (Some(bones), Some(skinned)) => Some((bones, skinned)),
+            _ => None,
+        };
+        Ok(ModelUniforms {
+            model: shader.locate_uniform("model")?,
+            material: MaterialUniforms::find(shader)?,
+            skinning,
+        })
+    }
+}
+
+impl Model {
+    /// Imports the model and its textures, blocking until everything is uploaded.
+    pub fn load_from_file<P: AsRef<Path>>(path: P) -> anyhow::Result<Self> {
+        let mut scene = SceneData::import(path)?.upload()?;
+        let textures = std::mem::take(&mut scene.texture_sources)
+            .into_iter()
+            .map(|source| {
+                let image = match source {
+                    TextureSource::File(filename) => ImageData::load_from_file(&filename)?,
+                    TextureSource::Image(image) => image,
+                };
+                Ok(Streamed::ready(image.upload()?))
+            })
+            .collect::<anyhow::Result<_>>()?;
+
+        Ok(Model {
+            scene: Streamed::ready(scene),
+            textures,
+            placeholders: placeholders()?,
+        })
+    }
+
+    /// Imports the model on `asset_loader`, call [`Model::update`] each frame to upload it.
+    pub fn stream_from_file<P: AsRef<Path>>(
+        asset_loader: &AssetLoader,
+        path: P,
+    ) -> anyhow::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        Ok(Model {
+            scene: asset_loader.load(move || SceneData::import(path)),
+            textures: Default::default(),
+            placeholders: placeholders()?,
+        })
+    }
+
+    /// Uploads whatever the workers have finished, starting on the textures once the scene
+    /// itself has arrived. Only failing to load the scene is an error, textures that fail stay
+    /// on their placeholder and their errors are returned instead.
+    pub fn update(
+        &mut self,
+        asset_loader: &AssetLoader,
+        budget: std::time::Duration,
+    ) -> anyhow::Result<Vec<anyhow::Error>> {
+        if self.scene.poll()? {
+            let scene = self.scene.get_mut().unwrap();
+            self.textures = std::mem::take(&mut scene.texture_sources)
+                .into_iter()
+                .map(|source| match source {
+                    TextureSource::File(filename) => {
+                        asset_loader.load(move || ImageData::load_from_file(&filename))
+                    }
+                    // Still goes through the loader so the upload counts against the budget
+                    TextureSource::Image(image) => asset_loader.load(move || Ok(image)),
+                })
+                .collect();
+        }
+
+        let deadline = std::time::Instant::now() + budget;
+        let mut texture_errors = Vec::new();
+        for texture in self
+            .textures
+            .iter_mut()
+            .filter(|texture| texture.is_pending())
+        {
+            if std::time::Instant::now() >= deadline {
+                break;
+            }
+            if let Err(error) = texture.poll() {
+                texture_errors.push(error);
+            }
+        }
+        Ok(texture_errors)
+    }
+
+    /// Whether the scene and all of its textures have been uploaded.
+    pub fn is_loaded(&self) -> bool {
+        self.scene.get().is_some() && self.textures.iter().all(|texture| !texture.is_pending())
+    }
+
+    /// `None` until the scene has been uploaded.
+    pub fn scene(&self) -> Option<&Scene> {
+        self.scene.get()
+    }
+
+    /// Call [`SceneGraph::update_transforms`] after changing node transforms, an
+    /// [`AnimationPlayer`] does this itself.
+    pub fn scene_mut(&mut self) -> Option<&mut Scene> {
+        self.scene.get_mut()
+    }
+
+    fn texture(&self, map: Option<TextureMap>, slot: usize) -> &TextureImage2D {
+        map.and_then(|map| self.textures.get(map.texture))
+            .and_then(|texture| texture.get())
+            .unwrap_or(&self.placeholders[slot])
+    }
+
+    /// Draws every node's meshes with `transform` times the node's world transform in the
+    /// `model` uniform, using the program `uniforms` were looked up from, which must be enabled.
+    ///
+    /// Each material map is bound to the `material.<map>` sampler, named as in
+    /// [`Material`], with its UV transform in `mat3 material.<map>_transform` and UV set in
+    /// `int material.<map>_uv_set`. The factors go to `material.base_colour_factor`,
+    /// `material.metallic_factor` and so on. Shaders only need to declare the uniforms they use.
+    ///
+    /// When the shader declares `uniform mat4 bones[MAX_BONES]` and `uniform bool skinned`,
+    /// skinned meshes are posed by the bone palette and drawn with just `transform` as the
+    /// model matrix. Other shaders draw them in their bind pose.
+    pub fn draw(&self, uniforms: &ModelUniforms, transform: &glm::Mat4) -> anyhow::Result<()> {
+        let Some(scene) = self.scene.get() else {
+            return Ok(());
+        };
+
+        let skinned_location = match uniforms.skinning {
+            Some((bones_location, skinned_location)) => {
+                let palette: Vec<f32> = scene
+                    .skeleton
+                    .palette(&scene.graph)
+                    .iter()
+                    .flat_map(|bone| bone.iter().copied())
+                    .collect();
+                opengl_sys::set_uniform_mat4_array(bones_location, false, &palette)?;
+                Some(skinned_location)
+            }
+            None => None,
+        };
+
+        for node in scene.graph.nodes() {
+            if node.meshes.is_empty() {
+                continue;
+            }
+            let node_model = transform * node.world_transform();
+
+            for scene_mesh in node
+                .meshes
+                .iter()
+                .filter_map(|&index| scene.meshes.get(index))
+            {
+                let skinned = skinned_location.is_some() && scene_mesh.skinned;
+                if let Some(skinned_location) = skinned_location {
+                    opengl_sys::set_uniform_i32(skinned_location, skinned as _)?;
+                }
+                let model = if skinned { transform } else { &node_model };
+                opengl_sys::set_uniform_mat4(uniforms.model, false, glm::value_ptr(model))?;
+
+                let material = &scene.materials[scene_mesh.material_index];
+                uniforms.material.set_factors(material)?;
+                for (slot, (map, uniforms)) in material
+                    .maps()
+                    .into_iter()
+                    .zip(&uniforms.material.maps)
+                    .enumerate()
+                {
+                    if let Some(unit) = uniforms.texture_unit {
+                        opengl_sys::active_texture(unit)?;
+                        self.texture(map, slot).bind()?;
+                        Sampler::unbind(unit)?;
+                    }
+                    if let Some(location) = uniforms.transform {
+                        let matrix = map.map_or_else(glm::identity, |map| map.transform.matrix());
+                        opengl_sys::set_uniform_mat3(location, false, glm::value_ptr(&matrix))?;
+                    }
+                    if let Some(location) = uniforms.uv_set {
+                        opengl_sys::set_uniform_i32(
+                            location,
+                            map.map_or(0, |map| map.uv_set) as _,
+                        )?;
+                    }
+                }
+                scene_mesh.mesh.draw(DrawMode::Triangles)?;
+            }
+        }
+        Ok(())
+    }
+}
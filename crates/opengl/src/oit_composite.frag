@@ -0,0 +1,19 @@
+#version 330 core
+
+out vec4 colour;
+
+uniform sampler2D opaque;
+uniform sampler2D accumulation;
+uniform sampler2D weight;
+
+void main() {
+    ivec2 texel = ivec2(gl_FragCoord.xy);
+    vec3 background = texelFetch(opaque, texel, 0).rgb;
+    vec4 accumulated = texelFetch(accumulation, texel, 0);
+    float weight_sum = texelFetch(weight, texel, 0).r;
+
+    // accumulated.a holds the product of (1 - alpha), i.e. how much background shows through
+    float revealage = accumulated.a;
+    vec3 average = accumulated.rgb / clamp(weight_sum, 1e-4, 5e4);
+    colour = vec4(mix(average, background, revealage), 1.0);
+}
\ No newline at end of file
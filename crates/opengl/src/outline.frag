@@ -0,0 +1,9 @@
+#version 330 core
+
+out vec4 colour;
+
+uniform vec3 outline_colour;
+
+void main() {
+    colour = vec4(outline_colour, 1.0);
+}
\ No newline at end of file
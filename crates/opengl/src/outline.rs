@@ -0,0 +1,84 @@
+use crate::mesh::*;
+use crate::render_state::*;
+use crate::shader::*;
+
+const OUTLINE_STENCIL_REFERENCE: i32 = 1;
+
+/// Highlights meshes with a flat coloured border using the stencil buffer.
+///
+/// The selected meshes are first drawn normally while marking the stencil buffer, then drawn
+/// again slightly enlarged in the outline colour everywhere that was not marked. Vertex
+/// positions must be at attribute 0, and the window needs stencil bits.
+pub struct Outline {
+    shader_program: ShaderProgram,
+    model: UniformLocation,
+    view: UniformLocation,
+    projection: UniformLocation,
+    scale: UniformLocation,
+    colour: UniformLocation,
+}
+
+impl Outline {
+    pub fn new(colour: &[f32; 3], scale: f32) -> anyhow::Result<Self> {
+        let shader_program = ShaderProgram::new(&[
+            Shader::new(include_str!("outline.vert"), ShaderType::Vertex)?,
+            Shader::new(include_str!("outline.frag"), ShaderType::Fragment)?,
+        ])?;
+
+        let outline = Outline {
+            model: shader_program.locate_uniform("model")?,
+            view: shader_program.locate_uniform("view")?,
+            projection: shader_program.locate_uniform("projection")?,
+            scale: shader_program.locate_uniform("scale")?,
+            colour: shader_program.locate_uniform("outline_colour")?,
+            shader_program,
+        };
+        outline.set_style(colour, scale)?;
+        Ok(outline)
+    }
+
+    /// `scale` enlarges each mesh about its own origin, so `1.05` gives a 5% border.
+    pub fn set_style(&self, colour: &[f32; 3], scale: f32) -> anyhow::Result<()> {
+        self.shader_program.enable()?;
+        opengl_sys::set_uniform_vec3(self.colour, colour)?;
+        opengl_sys::set_uniform_f32(self.scale, scale)?;
+        Ok(())
+    }
+
+    /// `draw_selected` renders the selected meshes with their usual shader, `selected` lists
+    /// the same meshes with their model matrices for the outline pass. `base` is restored after.
+    pub fn draw<F>(
+        &self,
+        render_state_cache: &mut RenderStateCache,
+        base: &RenderState,
+        view: &[f32],
+        projection: &[f32],
+        selected: &[(&Mesh, &[f32])],
+        draw_selected: F,
+    ) -> anyhow::Result<()>
+    where
+        F: FnOnce() -> anyhow::Result<()>,
+    {
+        render_state_cache.apply(&RenderState {
+            stencil: Some(Stencil::write(OUTLINE_STENCIL_REFERENCE)),
+            ..*base
+        })?;
+        draw_selected()?;
+
+        render_state_cache.apply(&RenderState {
+            depth_test: false,
+            stencil: Some(Stencil::not_equal(OUTLINE_STENCIL_REFERENCE)),
+            ..*base
+        })?;
+        self.shader_program.enable()?;
+        opengl_sys::set_uniform_mat4(self.view, false, view)?;
+        opengl_sys::set_uniform_mat4(self.projection, false, projection)?;
+        for (mesh, model) in selected {
+            opengl_sys::set_uniform_mat4(self.model, false, model)?;
+            mesh.draw(DrawMode::Triangles)?;
+        }
+
+        render_state_cache.apply(base)?;
+        Ok(())
+    }
+}
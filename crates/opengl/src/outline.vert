@@ -0,0 +1,12 @@
+#version 330 core
+
+layout (location=0) in vec3 position;
+
+uniform mat4 model;
+uniform mat4 view;
+uniform mat4 projection;
+uniform float scale;
+
+void main() {
+    gl_Position = projection * view * model * vec4(scale * position, 1.0);
+}
\ No newline at end of file
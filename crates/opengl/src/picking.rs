@@ -0,0 +1,93 @@
+use crate::frame_buffer::*;
+use crate::render_state::*;
+use crate::texture::*;
+
+/// Renders object IDs into an integer target, so the object under the cursor can be found by
+/// reading back a single pixel.
+///
+/// Draw pickable objects after [`PickingTarget::begin`] with a fragment shader writing
+/// `layout(location = 0) out uint id;`. IDs start at 1, 0 is left where nothing was drawn.
+pub struct PickingTarget {
+    frame_buffer: FrameBuffer,
+    ids: TextureImage2D,
+    depth: RenderBuffer,
+}
+
+impl PickingTarget {
+    pub fn new(width: u32, height: u32) -> anyhow::Result<Self> {
+        let target = Self {
+            frame_buffer: FrameBuffer::new(),
+            ids: TextureImage2D::new_empty(width, height, TextureFormat::R32UI)?,
+            depth: RenderBuffer::new(width, height, RenderBufferStorageFormat::Depth24Stencil8)?,
+        };
+
+        target.frame_buffer.bind()?;
+        target
+            .ids
+            .attach_to_frame_buffer(FrameBufferTarget::All, FrameBufferAttachment::Colour(0))?;
+        target
+            .depth
+            .attach_to_frame_buffer(FrameBufferTarget::All, FrameBufferAttachment::DepthStencil)?;
+        target.frame_buffer.check_complete()?;
+        FrameBuffer::bind_default()?;
+
+        Ok(target)
+    }
+
+    /// Call whenever the window changes size, a no-op if it hasn't.
+    pub fn resize(&mut self, width: u32, height: u32) -> anyhow::Result<()> {
+        if self.ids.size() == (width, height) {
+            return Ok(());
+        }
+
+        self.ids.resize(width, height)?;
+        self.depth.resize(width, height)?;
+        Ok(())
+    }
+
+    /// Binds and clears the target, draw the pickable objects after this.
+    pub fn begin(
+        &self,
+        render_state_cache: &mut RenderStateCache,
+        base: &RenderState,
+    ) -> anyhow::Result<()> {
+        self.frame_buffer.bind()?;
+        // Clears are affected by the depth write mask, and blending would mix IDs
+        render_state_cache.apply(&RenderState {
+            depth_test: true,
+            depth_write: true,
+            blend: None,
+            ..*base
+        })?;
+        opengl_sys::clear_draw_buffer_uint(0, &[0; 4])?;
+        opengl_sys::clear(opengl_sys::BufferBit::Depth)?;
+        Ok(())
+    }
+
+    /// The ID drawn at a position in pixels with the origin at the top left, the same units as
+    /// the drawable size the target is sized from. Mouse positions are in window coordinates,
+    /// convert them with `winman::InputState::to_pixels`. Leaves the window bound for drawing.
+    ///
+    /// Reading back waits for the GPU to finish drawing, so only pick when needed, e.g. on
+    /// click.
+    pub fn pick(&self, x: f32, y: f32) -> anyhow::Result<Option<u32>> {
+        let (width, height) = self.ids.size();
+        if x < 0.0 || y < 0.0 || x >= width as f32 || y >= height as f32 {
+            return Ok(None);
+        }
+
+        let mut id = [0u32];
+        self.frame_buffer.bind()?;
+        opengl_sys::read_pixels(
+            x as u32,
+            height - 1 - y as u32,
+            1,
+            1,
+            TextureFormat::RedInteger,
+            opengl_sys::DataType::U32,
+            &mut id,
+        )?;
+        FrameBuffer::bind_default()?;
+        Ok((id[0] != 0).then_some(id[0]))
+    }
+}
@@ -0,0 +1,331 @@
+pub use opengl_sys::{
+    BlendEquation, BlendFactor, CullFaceMode, DepthFunc, FrontFace, PolygonMode, StencilFunc,
+    StencilOp,
+};
+
+use opengl_sys::{BooleanParameter, Feature, FloatParameter, IntegerParameter};
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Blend {
+    pub source: BlendFactor,
+    pub destination: BlendFactor,
+    pub source_alpha: BlendFactor,
+    pub destination_alpha: BlendFactor,
+    pub equation: BlendEquation,
+}
+
+impl Blend {
+    /// The same factors for colour and alpha.
+    pub fn new(source: BlendFactor, destination: BlendFactor) -> Self {
+        Blend {
+            source,
+            destination,
+            source_alpha: source,
+            destination_alpha: destination,
+            equation: BlendEquation::Add,
+        }
+    }
+
+    /// Classic "over" compositing for non-premultiplied alpha.
+    pub fn alpha() -> Self {
+        Self::new(BlendFactor::SourceAlpha, BlendFactor::OneMinusSourceAlpha)
+    }
+
+    /// "Over" compositing for colours already multiplied by their alpha.
+    pub fn premultiplied_alpha() -> Self {
+        Self::new(BlendFactor::One, BlendFactor::OneMinusSourceAlpha)
+    }
+
+    /// Sums colours, e.g. for particles and glows.
+    pub fn additive() -> Self {
+        Self::new(BlendFactor::One, BlendFactor::One)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Stencil {
+    pub func: StencilFunc,
+    pub reference: i32,
+    pub read_mask: u32,
+    pub write_mask: u32,
+    pub stencil_fail: StencilOp,
+    pub depth_fail: StencilOp,
+    pub pass: StencilOp,
+}
+
+impl Stencil {
+    /// Writes `reference` wherever something is drawn.
+    pub fn write(reference: i32) -> Self {
+        Stencil {
+            func: StencilFunc::Always,
+            reference,
+            read_mask: 0xFF,
+            write_mask: 0xFF,
+            stencil_fail: StencilOp::Keep,
+            depth_fail: StencilOp::Keep,
+            pass: StencilOp::Replace,
+        }
+    }
+
+    /// Only draws where the stencil buffer does not already hold `reference`, leaving it intact.
+    pub fn not_equal(reference: i32) -> Self {
+        Stencil {
+            func: StencilFunc::NotEqual,
+            reference,
+            read_mask: 0xFF,
+            write_mask: 0x00,
+            stencil_fail: StencilOp::Keep,
+            depth_fail: StencilOp::Keep,
+            pass: StencilOp::Keep,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Scissor {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Fixed function state for a draw. `None` disables the matching test or stage.
+///
+/// The default matches the initial state of a fresh GL context.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct RenderState {
+    pub depth_test: bool,
+    pub depth_func: DepthFunc,
+    pub depth_write: bool,
+    pub blend: Option<Blend>,
+    pub cull_face: Option<CullFaceMode>,
+    pub front_face: FrontFace,
+    pub stencil: Option<Stencil>,
+    pub scissor: Option<Scissor>,
+    pub polygon_mode: PolygonMode,
+    pub line_width: f32,
+}
+
+impl Default for RenderState {
+    fn default() -> Self {
+        RenderState {
+            depth_test: false,
+            depth_func: DepthFunc::Less,
+            depth_write: true,
+            blend: None,
+            cull_face: None,
+            front_face: FrontFace::CounterClockwise,
+            stencil: None,
+            scissor: None,
+            polygon_mode: PolygonMode::Fill,
+            line_width: 1.0,
+        }
+    }
+}
+
+impl RenderState {
+    /// Reads back the state the context is currently in.
+    pub fn query() -> anyhow::Result<Self> {
+        let blend = if opengl_sys::is_enabled(Feature::Blend)? {
+            Some(Blend {
+                source: query_enum(IntegerParameter::BlendSourceRGB)?,
+                destination: query_enum(IntegerParameter::BlendDestinationRGB)?,
+                source_alpha: query_enum(IntegerParameter::BlendSourceAlpha)?,
+                destination_alpha: query_enum(IntegerParameter::BlendDestinationAlpha)?,
+                equation: query_enum(IntegerParameter::BlendEquationRGB)?,
+            })
+        } else {
+            None
+        };
+
+        let cull_face = if opengl_sys::is_enabled(Feature::CullFace)? {
+            Some(query_enum(IntegerParameter::CullFaceMode)?)
+        } else {
+            None
+        };
+
+        let stencil = if opengl_sys::is_enabled(Feature::StencilTest)? {
+            Some(Stencil {
+                func: query_enum(IntegerParameter::StencilFunc)?,
+                reference: opengl_sys::get_integer(IntegerParameter::StencilReference)?,
+                read_mask: opengl_sys::get_integer(IntegerParameter::StencilValueMask)? as _,
+                write_mask: opengl_sys::get_integer(IntegerParameter::StencilWriteMask)? as _,
+                stencil_fail: query_enum(IntegerParameter::StencilFail)?,
+                depth_fail: query_enum(IntegerParameter::StencilPassDepthFail)?,
+                pass: query_enum(IntegerParameter::StencilPassDepthPass)?,
+            })
+        } else {
+            None
+        };
+
+        let scissor = if opengl_sys::is_enabled(Feature::ScissorTest)? {
+            let [x, y, width, height] = opengl_sys::get_integers(IntegerParameter::ScissorBox)?;
+            Some(Scissor {
+                x,
+                y,
+                width: width as _,
+                height: height as _,
+            })
+        } else {
+            None
+        };
+
+        Ok(RenderState {
+            depth_test: opengl_sys::is_enabled(Feature::DepthTest)?,
+            depth_func: query_enum(IntegerParameter::DepthFunc)?,
+            depth_write: opengl_sys::get_boolean(BooleanParameter::DepthWriteMask)?,
+            blend,
+            cull_face,
+            front_face: query_enum(IntegerParameter::FrontFace)?,
+            stencil,
+            scissor,
+            polygon_mode: query_enum(IntegerParameter::PolygonMode)?,
+            line_width: opengl_sys::get_float(FloatParameter::LineWidth)?,
+        })
+    }
+
+    /// Issues only the calls needed to move the context from `current` to this state.
+    pub fn apply_from(&self, current: &RenderState) -> anyhow::Result<()> {
+        set_feature(Feature::DepthTest, self.depth_test, current.depth_test)?;
+        if self.depth_func != current.depth_func {
+            opengl_sys::set_depth_func(self.depth_func);
+        }
+        if self.depth_write != current.depth_write {
+            opengl_sys::set_depth_mask(self.depth_write)?;
+        }
+
+        set_feature(
+            Feature::Blend,
+            self.blend.is_some(),
+            current.blend.is_some(),
+        )?;
+        if let Some(blend) = self.blend {
+            let func = |blend: Blend| {
+                (
+                    blend.source,
+                    blend.destination,
+                    blend.source_alpha,
+                    blend.destination_alpha,
+                )
+            };
+            if current.blend.map(func) != Some(func(blend)) {
+                opengl_sys::set_blend_func_separate(
+                    blend.source,
+                    blend.destination,
+                    blend.source_alpha,
+                    blend.destination_alpha,
+                )?;
+            }
+            if current.blend.map(|current| current.equation) != Some(blend.equation) {
+                opengl_sys::set_blend_equation(blend.equation)?;
+            }
+        }
+
+        set_feature(
+            Feature::CullFace,
+            self.cull_face.is_some(),
+            current.cull_face.is_some(),
+        )?;
+        if let Some(cull_face) = self.cull_face {
+            if current.cull_face != Some(cull_face) {
+                opengl_sys::set_cull_face(cull_face)?;
+            }
+        }
+        if self.front_face != current.front_face {
+            opengl_sys::set_front_face(self.front_face)?;
+        }
+
+        set_feature(
+            Feature::StencilTest,
+            self.stencil.is_some(),
+            current.stencil.is_some(),
+        )?;
+        if let Some(stencil) = self.stencil {
+            let current_func = current
+                .stencil
+                .map(|current| (current.func, current.reference, current.read_mask));
+            if current_func != Some((stencil.func, stencil.reference, stencil.read_mask)) {
+                opengl_sys::set_stencil_func(stencil.func, stencil.reference, stencil.read_mask)?;
+            }
+            let current_op = current
+                .stencil
+                .map(|current| (current.stencil_fail, current.depth_fail, current.pass));
+            if current_op != Some((stencil.stencil_fail, stencil.depth_fail, stencil.pass)) {
+                opengl_sys::set_stencil_op(stencil.stencil_fail, stencil.depth_fail, stencil.pass)?;
+            }
+            if current.stencil.map(|current| current.write_mask) != Some(stencil.write_mask) {
+                opengl_sys::set_stencil_mask(stencil.write_mask)?;
+            }
+        } else if let Some(current) = current.stencil {
+            // The write mask also applies to clears, so don't leave a restrictive one behind
+            if current.write_mask != u32::MAX {
+                opengl_sys::set_stencil_mask(u32::MAX)?;
+            }
+        }
+
+        set_feature(
+            Feature::ScissorTest,
+            self.scissor.is_some(),
+            current.scissor.is_some(),
+        )?;
+        if let Some(scissor) = self.scissor {
+            if current.scissor != Some(scissor) {
+                opengl_sys::set_scissor(scissor.x, scissor.y, scissor.width, scissor.height)?;
+            }
+        }
+
+        if self.polygon_mode != current.polygon_mode {
+            opengl_sys::set_polygon_mode(self.polygon_mode)?;
+        }
+        if self.line_width != current.line_width {
+            opengl_sys::set_line_width(self.line_width)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Remembers the last applied [`RenderState`] so switching between states is cheap.
+pub struct RenderStateCache {
+    current: RenderState,
+}
+
+impl RenderStateCache {
+    pub fn new() -> anyhow::Result<Self> {
+        Ok(RenderStateCache {
+            current: RenderState::query()?,
+        })
+    }
+
+    pub fn apply(&mut self, state: &RenderState) -> anyhow::Result<()> {
+        state.apply_from(&self.current)?;
+        self.current = *state;
+        Ok(())
+    }
+
+    pub fn current(&self) -> &RenderState {
+        &self.current
+    }
+
+    /// Call after changing state outside of the cache, e.g. through raw `opengl_sys` calls.
+    pub fn resync(&mut self) -> anyhow::Result<()> {
+        self.current = RenderState::query()?;
+        Ok(())
+    }
+}
+
+fn set_feature(feature: Feature, enabled: bool, currently_enabled: bool) -> anyhow::Result<()> {
+    match (enabled, currently_enabled) {
+        (true, false) => opengl_sys::enable(feature)?,
+        (false, true) => opengl_sys::disable(feature)?,
+        _ => {}
+    }
+    Ok(())
+}
+
+fn query_enum<T>(parameter: IntegerParameter) -> anyhow::Result<T>
+where
+    T: TryFrom<u32, Error = opengl_sys::Error>,
+{
+    Ok(T::try_from(opengl_sys::get_integer(parameter)? as u32)?)
+}
@@ -0,0 +1,48 @@
+pub use opengl_sys::{TextureParameterName, TextureParameterValue};
+
+/// Sampling state that lives independently of any texture, so the same texture can be read
+/// with different wrapping or filtering depending on the sampler bound alongside it.
+pub struct Sampler {
+    id: opengl_sys::SamplerID,
+}
+
+impl Sampler {
+    pub fn new(
+        parameters: &[(TextureParameterName, TextureParameterValue)],
+    ) -> anyhow::Result<Self> {
+        let sampler = Self {
+            id: opengl_sys::create_sampler(),
+        };
+
+        for (name, value) in parameters {
+            opengl_sys::set_sampler_parameter_value(sampler.id, *name, *value)?;
+        }
+
+        Ok(sampler)
+    }
+
+    pub fn set_parameter(
+        &self,
+        name: TextureParameterName,
+        value: TextureParameterValue,
+    ) -> anyhow::Result<()> {
+        opengl_sys::set_sampler_parameter_value(self.id, name, value)?;
+        Ok(())
+    }
+
+    pub fn bind(&self, index: u32) -> anyhow::Result<()> {
+        opengl_sys::bind_sampler(index, self.id)?;
+        Ok(())
+    }
+
+    pub fn unbind(index: u32) -> anyhow::Result<()> {
+        opengl_sys::bind_sampler(index, 0)?;
+        Ok(())
+    }
+}
+
+impl Drop for Sampler {
+    fn drop(&mut self) {
+        opengl_sys::delete_sampler(self.id).expect("Failed to delete sampler");
+    }
+}
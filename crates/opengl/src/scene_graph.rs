@@ -0,0 +1,109 @@
+use nalgebra_glm as glm;
+
+pub type NodeId = usize;
+
+pub struct Node {
+    pub name: String,
+    pub parent: Option<NodeId>,
+    pub children: Vec<NodeId>,
+    /// Indices of the meshes drawn at this node.
+    pub meshes: Vec<usize>,
+    local: glm::Mat4,
+    world: glm::Mat4,
+    rest: glm::Mat4,
+}
+
+impl Node {
+    /// Relative to the parent node.
+    pub fn local_transform(&self) -> &glm::Mat4 {
+        &self.local
+    }
+
+    /// The local transform the node was created with, e.g. the bind pose of a skeleton.
+    pub fn rest_transform(&self) -> &glm::Mat4 {
+        &self.rest
+    }
+
+    /// As of the last [`SceneGraph::update_transforms`].
+    pub fn world_transform(&self) -> &glm::Mat4 {
+        &self.world
+    }
+}
+
+/// Nodes are stored parents first, so world transforms can be updated in a single pass.
+#[derive(Default)]
+pub struct SceneGraph {
+    nodes: Vec<Node>,
+    dirty: bool,
+}
+
+impl SceneGraph {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn add_node(
+        &mut self,
+        parent: Option<NodeId>,
+        name: &str,
+        local: glm::Mat4,
+        meshes: Vec<usize>,
+    ) -> NodeId {
+        let id = self.nodes.len();
+        let parent = parent.filter(|&parent| parent < id);
+        if let Some(parent) = parent {
+            self.nodes[parent].children.push(id);
+        }
+
+        let world = parent.map_or(local, |parent| self.nodes[parent].world * local);
+        self.nodes.push(Node {
+            name: name.to_string(),
+            parent,
+            children: Default::default(),
+            meshes,
+            local,
+            world,
+            rest: local,
+        });
+        id
+    }
+
+    pub fn node(&self, id: NodeId) -> Option<&Node> {
+        self.nodes.get(id)
+    }
+
+    pub fn nodes(&self) -> &[Node] {
+        &self.nodes
+    }
+
+    pub fn roots(&self) -> impl Iterator<Item = NodeId> + '_ {
+        (0..self.nodes.len()).filter(|&id| self.nodes[id].parent.is_none())
+    }
+
+    /// First node with the given name, names aren't required to be unique.
+    pub fn find(&self, name: &str) -> Option<NodeId> {
+        self.nodes.iter().position(|node| node.name == name)
+    }
+
+    pub fn set_local_transform(&mut self, id: NodeId, local: glm::Mat4) {
+        if let Some(node) = self.nodes.get_mut(id) {
+            node.local = local;
+            self.dirty = true;
+        }
+    }
+
+    /// Propagates changed local transforms down to the world transforms.
+    pub fn update_transforms(&mut self) {
+        if !self.dirty {
+            return;
+        }
+        for id in 0..self.nodes.len() {
+            let parent_world = self.nodes[id]
+                .parent
+                .map_or_else(glm::identity, |parent| self.nodes[parent].world);
+            let node = &mut self.nodes[id];
+            node.world = parent_world * node.local;
+        }
+        self.dirty = false;
+    }
+}
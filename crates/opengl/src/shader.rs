@@ -1,6 +1,7 @@
 pub use opengl_sys::ShaderType;
 
 use crate::error::*;
+use std::collections::HashMap;
 
 pub use opengl_sys::UniformLocation;
 
@@ -10,6 +11,7 @@ pub struct Shader {
 
 pub struct ShaderProgram {
     id: opengl_sys::ProgramID,
+    texture_units: HashMap<String, u32>,
 }
 
 impl Drop for Shader {
@@ -49,8 +51,9 @@ impl Shader {
 
 impl ShaderProgram {
     pub fn new(shaders: &[Shader]) -> anyhow::Result<Self> {
-        let shader_program = ShaderProgram {
+        let mut shader_program = ShaderProgram {
             id: opengl_sys::create_program(),
+            texture_units: Default::default(),
         };
 
         for shader in shaders {
@@ -68,6 +71,7 @@ impl ShaderProgram {
             opengl_sys::ProgramParameter::LinkStatus,
         )? != 0
         {
+            shader_program.texture_units = shader_program.assign_texture_units()?;
             Ok(shader_program)
         } else {
             let buffer_size = opengl_sys::get_program_paramter(
@@ -89,4 +93,59 @@ impl ShaderProgram {
         let location = opengl_sys::get_uniform_location(self.id, name)?;
         Ok(location.ok_or_else(|| Error::MissingUniform(name.into()))?)
     }
+
+    /// Like [`ShaderProgram::locate_uniform`], but `None` when the program doesn't use `name`.
+    pub fn find_uniform(&self, name: &str) -> anyhow::Result<Option<UniformLocation>> {
+        Ok(opengl_sys::get_uniform_location(self.id, name)?)
+    }
+
+    /// The texture unit assigned to the sampler uniform `name` when the program was linked.
+    pub fn texture_unit(&self, name: &str) -> anyhow::Result<u32> {
+        let unit = self.texture_units.get(name).copied();
+        Ok(unit.ok_or_else(|| Error::MissingUniform(name.into()))?)
+    }
+
+    /// Gives every active sampler uniform, including each element of sampler arrays, its own
+    /// texture unit and uploads the assignment once, so draws only need to bind textures.
+    fn assign_texture_units(&self) -> anyhow::Result<HashMap<String, u32>> {
+        let uniform_count = opengl_sys::get_program_paramter(
+            self.id,
+            opengl_sys::ProgramParameter::ActiveUniforms,
+        )?;
+        let max_name_length = opengl_sys::get_program_paramter(
+            self.id,
+            opengl_sys::ProgramParameter::ActiveUniformMaxLength,
+        )?;
+
+        let mut sampler_names = Vec::new();
+        for index in 0..uniform_count {
+            let (name, size, uniform_type) =
+                opengl_sys::get_active_uniform(self.id, index, max_name_length)?;
+            if !uniform_type.is_sampler() {
+                continue;
+            }
+            match name.strip_suffix("[0]") {
+                Some(base) => sampler_names.extend((0..size).map(|i| format!("{base}[{i}]"))),
+                None => sampler_names.push(name),
+            }
+        }
+        sampler_names.sort();
+
+        let max_units =
+            opengl_sys::get_integer(opengl_sys::IntegerParameter::MaxCombinedTextureImageUnits)?
+                as usize;
+        if sampler_names.len() > max_units {
+            anyhow::bail!(Error::TooManyTextureUnits(sampler_names.len(), max_units));
+        }
+
+        self.enable()?;
+        let mut texture_units = HashMap::with_capacity(sampler_names.len());
+        for (unit, name) in sampler_names.into_iter().enumerate() {
+            let location = self.locate_uniform(&name)?;
+            opengl_sys::set_uniform_i32(location, unit as _)?;
+            texture_units.insert(name, unit as u32);
+        }
+
+        Ok(texture_units)
+    }
 }
@@ -1,41 +1,162 @@
 use crate::error::*;
+use crate::loader::*;
+use crate::sampler::*;
 use crate::shader::*;
 use anyhow::Context;
 use image::DynamicImage;
 use opengl_sys::DataType;
 use std::path::Path;
 
+pub use opengl_sys::{FrameBufferAttachment, FrameBufferTarget, TextureFormat};
+
 pub trait TextureType {
     fn bind(&self) -> anyhow::Result<()>;
 }
 
-pub struct ShaderProgramTexture<'a, Texture: TextureType> {
+pub struct ShaderProgramTexture<'a, Texture: TextureType + ?Sized> {
     texture: &'a Texture,
+    sampler: Option<&'a Sampler>,
     index: u32,
 }
 
-impl<'a, Texture: TextureType> ShaderProgramTexture<'a, Texture> {
+impl<'a, Texture: TextureType + ?Sized> ShaderProgramTexture<'a, Texture> {
     pub fn new(
         texture: &'a Texture,
         shader_program: &ShaderProgram,
         name: &str,
-        index: u32,
     ) -> anyhow::Result<Self> {
-        shader_program.enable()?;
-        let location = shader_program.locate_uniform(name)?;
-        opengl_sys::set_uniform_i32(location, index as _)?;
-        Ok(Self { texture, index })
+        Ok(Self {
+            texture,
+            sampler: None,
+            index: shader_program.texture_unit(name)?,
+        })
+    }
+
+    /// Samples the texture through `sampler` instead of its own parameters.
+    pub fn with_sampler(mut self, sampler: &'a Sampler) -> Self {
+        self.sampler = Some(sampler);
+        self
     }
 
     pub fn draw(&self) -> anyhow::Result<()> {
         opengl_sys::active_texture(self.index)?;
         self.texture.bind()?;
+        match self.sampler {
+            Some(sampler) => sampler.bind(self.index)?,
+            None => Sampler::unbind(self.index)?,
+        }
+        Ok(())
+    }
+}
+
+/// Every texture a program samples from for one draw, bound together through a single call.
+pub struct TextureBindings<'a> {
+    shader_program: &'a ShaderProgram,
+    textures: Vec<ShaderProgramTexture<'a, dyn TextureType + 'a>>,
+}
+
+impl<'a> TextureBindings<'a> {
+    pub fn new(shader_program: &'a ShaderProgram) -> Self {
+        Self {
+            shader_program,
+            textures: Default::default(),
+        }
+    }
+
+    pub fn with(mut self, name: &str, texture: &'a dyn TextureType) -> anyhow::Result<Self> {
+        let texture = ShaderProgramTexture::new(texture, self.shader_program, name)?;
+        self.textures.push(texture);
+        Ok(self)
+    }
+
+    pub fn with_sampler(
+        mut self,
+        name: &str,
+        texture: &'a dyn TextureType,
+        sampler: &'a Sampler,
+    ) -> anyhow::Result<Self> {
+        let texture =
+            ShaderProgramTexture::new(texture, self.shader_program, name)?.with_sampler(sampler);
+        self.textures.push(texture);
+        Ok(self)
+    }
+
+    pub fn bind(&self) -> anyhow::Result<()> {
+        for texture in &self.textures {
+            texture.draw()?;
+        }
         Ok(())
     }
 }
 
+/// Decoded pixels ready to become a [`TextureImage2D`], safe to produce off the GL thread.
+pub struct ImageData {
+    data: Vec<u8>,
+    format: TextureFormat,
+    width: u32,
+    height: u32,
+}
+
+impl ImageData {
+    /// `data` holds tightly packed rows, starting with the one sampled at `v = 0`.
+    pub fn new(data: Vec<u8>, format: TextureFormat, width: u32, height: u32) -> Self {
+        Self {
+            data,
+            format,
+            width,
+            height,
+        }
+    }
+
+    pub fn load_from_file<P>(texture_filename: &P) -> anyhow::Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let (image, format) = load_image(texture_filename, true)?;
+        Ok(Self {
+            width: image.width(),
+            height: image.height(),
+            data: image.into_bytes(),
+            format,
+        })
+    }
+
+    /// Marks 8-bit colour data as sRGB encoded, so it's converted to linear when sampled.
+    pub fn into_srgb(mut self) -> Self {
+        self.format = match self.format {
+            TextureFormat::RGB | TextureFormat::RGB8 => TextureFormat::SRGB8,
+            TextureFormat::RGBA | TextureFormat::RGBA8 => TextureFormat::SRGB8Alpha8,
+            format => format,
+        };
+        self
+    }
+
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    pub fn format(&self) -> TextureFormat {
+        self.format
+    }
+}
+
+impl Upload for ImageData {
+    type Output = TextureImage2D;
+
+    fn upload(self) -> anyhow::Result<TextureImage2D> {
+        TextureImage2D::load_from_memory(&self.data, self.format, self.width, self.height)
+    }
+}
+
 pub struct TextureImage2D {
     id: opengl_sys::TextureID,
+    format: TextureFormat,
+    width: u32,
+    height: u32,
 }
 
 impl TextureImage2D {
@@ -43,18 +164,25 @@ impl TextureImage2D {
     where
         P: AsRef<Path>,
     {
-        let (image, format) = load_image(texture_filename, true)?;
-        Self::load_from_memory(image.as_bytes(), format, image.width(), image.height())
+        ImageData::load_from_file(texture_filename)?.upload()
+    }
+
+    /// A single pixel texture, useful as a stand-in while the real one is still loading.
+    pub fn solid_colour(rgba: [u8; 4]) -> anyhow::Result<Self> {
+        Self::load_from_memory(&rgba, TextureFormat::RGBA, 1, 1)
     }
 
     pub fn load_from_memory(
         data: &[u8],
-        format: opengl_sys::TextureFormat,
+        format: TextureFormat,
         width: u32,
         height: u32,
     ) -> anyhow::Result<Self> {
         let texture = Self {
             id: opengl_sys::create_texture(),
+            format,
+            width,
+            height,
         };
 
         opengl_sys::bind_texture(texture.id, opengl_sys::TextureTarget::Image2D)?;
@@ -81,17 +209,139 @@ impl TextureImage2D {
         opengl_sys::load_texture_image2d(
             opengl_sys::TextureTarget::Image2D,
             0,
-            opengl_sys::TextureFormat::RGB,
+            internal_format(format),
             width as _,
             height as _,
-            format,
-            DataType::U8,
+            pixel_data_format(format),
+            pixel_data_type(format),
             Some(data),
         )?;
         opengl_sys::generate_mipmaps(opengl_sys::TextureTarget::Image2D)?;
 
         Ok(texture)
     }
+
+    /// Allocates storage without any initial data, for use as a render target or a texture
+    /// whose contents are streamed in later through [`TextureImage2D::update_region`].
+    pub fn new_empty(width: u32, height: u32, format: TextureFormat) -> anyhow::Result<Self> {
+        let mut texture = Self {
+            id: opengl_sys::create_texture(),
+            format,
+            width,
+            height,
+        };
+
+        opengl_sys::bind_texture(texture.id, opengl_sys::TextureTarget::Image2D)?;
+        opengl_sys::set_texture_parameter_value(
+            opengl_sys::TextureTarget::Image2D,
+            opengl_sys::TextureParameterName::WrapS,
+            opengl_sys::TextureParameterValue::ClampToEdge,
+        )?;
+        opengl_sys::set_texture_parameter_value(
+            opengl_sys::TextureTarget::Image2D,
+            opengl_sys::TextureParameterName::WrapT,
+            opengl_sys::TextureParameterValue::ClampToEdge,
+        )?;
+        opengl_sys::set_texture_parameter_value(
+            opengl_sys::TextureTarget::Image2D,
+            opengl_sys::TextureParameterName::MinFilter,
+            opengl_sys::TextureParameterValue::Linear,
+        )?;
+        opengl_sys::set_texture_parameter_value(
+            opengl_sys::TextureTarget::Image2D,
+            opengl_sys::TextureParameterName::MagFilter,
+            opengl_sys::TextureParameterValue::Linear,
+        )?;
+        texture.resize(width, height)?;
+
+        Ok(texture)
+    }
+
+    /// Replaces the pixels of a sub-rectangle, `data` holds tightly packed rows in the texture's
+    /// format like [`ImageData`].
+    pub fn update_region(
+        &self,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+        data: &[u8],
+    ) -> anyhow::Result<()> {
+        let fits = |start: u32, size: u32, limit: u32| {
+            start.checked_add(size).is_some_and(|end| end <= limit)
+        };
+        if !fits(x, width, self.width) || !fits(y, height, self.height) {
+            anyhow::bail!(Error::TextureRegionOutOfBounds(
+                (x, y, width, height),
+                (self.width, self.height)
+            ));
+        }
+
+        let expected = opengl_sys::pixel_data_size(
+            width,
+            height,
+            pixel_data_format(self.format),
+            pixel_data_type(self.format),
+        );
+        if data.len() != expected {
+            anyhow::bail!(Error::TextureDataSize(data.len(), expected));
+        }
+
+        opengl_sys::bind_texture(self.id, opengl_sys::TextureTarget::Image2D)?;
+        opengl_sys::load_texture_sub_image2d(
+            opengl_sys::TextureTarget::Image2D,
+            0,
+            x as _,
+            y as _,
+            width as _,
+            height as _,
+            pixel_data_format(self.format),
+            pixel_data_type(self.format),
+            data,
+        )?;
+        Ok(())
+    }
+
+    /// Reallocates storage at the new size, the previous contents are discarded.
+    pub fn resize(&mut self, width: u32, height: u32) -> anyhow::Result<()> {
+        opengl_sys::bind_texture(self.id, opengl_sys::TextureTarget::Image2D)?;
+        opengl_sys::load_texture_image2d(
+            opengl_sys::TextureTarget::Image2D,
+            0,
+            internal_format(self.format),
+            width as _,
+            height as _,
+            pixel_data_format(self.format),
+            pixel_data_type(self.format),
+            None as Option<&[u8]>,
+        )?;
+        self.width = width;
+        self.height = height;
+        Ok(())
+    }
+
+    pub fn attach_to_frame_buffer(
+        &self,
+        target: FrameBufferTarget,
+        attachment: FrameBufferAttachment,
+    ) -> anyhow::Result<()> {
+        opengl_sys::frame_buffer_texture_2d(
+            target,
+            attachment,
+            opengl_sys::TextureTarget::Image2D,
+            self.id,
+            0,
+        )?;
+        Ok(())
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    pub fn format(&self) -> TextureFormat {
+        self.format
+    }
 }
 
 impl TextureType for TextureImage2D {
@@ -107,6 +357,99 @@ impl Drop for TextureImage2D {
     }
 }
 
+/// A render target with several samples per texel. It can't be filtered, so either resolve it
+/// into a [`TextureImage2D`] or read individual samples through a `sampler2DMS`.
+pub struct TextureImage2DMultisample {
+    id: opengl_sys::TextureID,
+    format: TextureFormat,
+    samples: u32,
+    width: u32,
+    height: u32,
+}
+
+impl TextureImage2DMultisample {
+    pub fn new_empty(
+        width: u32,
+        height: u32,
+        format: TextureFormat,
+        samples: u32,
+    ) -> anyhow::Result<Self> {
+        let mut texture = Self {
+            id: opengl_sys::create_texture(),
+            format,
+            samples: check_sample_count(samples)?,
+            width,
+            height,
+        };
+        texture.resize(width, height)?;
+        Ok(texture)
+    }
+
+    /// Reallocates storage at the new size, the previous contents are discarded.
+    pub fn resize(&mut self, width: u32, height: u32) -> anyhow::Result<()> {
+        opengl_sys::bind_texture(self.id, opengl_sys::TextureTarget::Image2DMultisample)?;
+        opengl_sys::load_texture_image2d_multisample(
+            self.samples,
+            internal_format(self.format),
+            width as _,
+            height as _,
+            true,
+        )?;
+        self.width = width;
+        self.height = height;
+        Ok(())
+    }
+
+    pub fn attach_to_frame_buffer(
+        &self,
+        target: FrameBufferTarget,
+        attachment: FrameBufferAttachment,
+    ) -> anyhow::Result<()> {
+        opengl_sys::frame_buffer_texture_2d(
+            target,
+            attachment,
+            opengl_sys::TextureTarget::Image2DMultisample,
+            self.id,
+            0,
+        )?;
+        Ok(())
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    pub fn format(&self) -> TextureFormat {
+        self.format
+    }
+
+    pub fn samples(&self) -> u32 {
+        self.samples
+    }
+}
+
+impl TextureType for TextureImage2DMultisample {
+    fn bind(&self) -> anyhow::Result<()> {
+        opengl_sys::bind_texture(self.id, opengl_sys::TextureTarget::Image2DMultisample)?;
+        Ok(())
+    }
+}
+
+impl Drop for TextureImage2DMultisample {
+    fn drop(&mut self) {
+        opengl_sys::delete_texture(self.id).expect("Failed to delete multisample texture");
+    }
+}
+
+/// Fails for 0 or if the implementation can't allocate `samples` samples per pixel.
+pub fn check_sample_count(samples: u32) -> anyhow::Result<u32> {
+    let max_samples = opengl_sys::get_integer(opengl_sys::IntegerParameter::MaxSamples)? as u32;
+    if samples == 0 || samples > max_samples {
+        anyhow::bail!(Error::UnsupportedSampleCount(samples, max_samples));
+    }
+    Ok(samples)
+}
+
 pub struct CubeMap<Data> {
     pub right: Data,
     pub left: Data,
@@ -209,10 +552,47 @@ impl Drop for TextureCubeMap {
     }
 }
 
+/// Sized storage for the unsized 8-bit colour formats, other formats are used as they are.
+fn internal_format(format: TextureFormat) -> TextureFormat {
+    match format {
+        TextureFormat::RGB => TextureFormat::RGB8,
+        TextureFormat::RGBA => TextureFormat::RGBA8,
+        format => format,
+    }
+}
+
+/// Sized internal formats are only valid for storage, uploads name the matching base format.
+fn pixel_data_format(format: TextureFormat) -> TextureFormat {
+    match format {
+        TextureFormat::RGB8 | TextureFormat::SRGB8 => TextureFormat::RGB,
+        TextureFormat::RGBA8 | TextureFormat::SRGB8Alpha8 => TextureFormat::RGBA,
+        TextureFormat::R16F => TextureFormat::Red,
+        TextureFormat::RGBA16F => TextureFormat::RGBA,
+        TextureFormat::R32UI => TextureFormat::RedInteger,
+        format => format,
+    }
+}
+
+fn pixel_data_type(format: TextureFormat) -> DataType {
+    match format {
+        TextureFormat::Red
+        | TextureFormat::RGB
+        | TextureFormat::RGBA
+        | TextureFormat::RGB8
+        | TextureFormat::RGBA8
+        | TextureFormat::SRGB8
+        | TextureFormat::SRGB8Alpha8 => DataType::U8,
+        TextureFormat::R32UI | TextureFormat::RedInteger => DataType::U32,
+        TextureFormat::R16F | TextureFormat::RGBA16F => DataType::F32,
+        TextureFormat::DepthComponent => DataType::F32,
+        TextureFormat::DepthStencil => DataType::U24U8,
+    }
+}
+
 fn load_image<P>(
     filename: &P,
     flip_verticals: bool,
-) -> anyhow::Result<(DynamicImage, opengl_sys::TextureFormat)>
+) -> anyhow::Result<(DynamicImage, TextureFormat)>
 where
     P: AsRef<Path>,
 {
@@ -222,8 +602,8 @@ where
     let image = if flip_verticals { image.flipv() } else { image };
 
     let format = match image.color() {
-        image::ColorType::Rgb8 => Ok(opengl_sys::TextureFormat::RGB),
-        image::ColorType::Rgba8 => Ok(opengl_sys::TextureFormat::RGBA),
+        image::ColorType::Rgb8 => Ok(TextureFormat::RGB),
+        image::ColorType::Rgba8 => Ok(TextureFormat::RGBA),
         _ => Err(Error::InvalidImageColourType(
             filename.as_ref().into(),
             image.color(),
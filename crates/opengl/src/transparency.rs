@@ -0,0 +1,243 @@
+use crate::frame_buffer::*;
+use crate::mesh::*;
+use crate::render_state::*;
+use crate::shader::*;
+use crate::texture::*;
+
+const ACCUMULATION_ATTACHMENT: u32 = 0;
+const WEIGHT_ATTACHMENT: u32 = 1;
+const OPAQUE_ATTACHMENT: u32 = 2;
+
+type Draw<'a> = Box<dyn FnOnce() -> anyhow::Result<()> + 'a>;
+
+/// Collects blended draws for a frame and issues them farthest from the camera first, so each
+/// surface is composited over everything behind it.
+///
+/// Sorting is per draw, not per triangle, so intersecting or self-overlapping transparent
+/// meshes can still show artefacts; [`WeightedBlendedOit`] avoids sorting entirely.
+pub struct TransparentQueue<'a> {
+    camera_position: [f32; 3],
+    blend: Blend,
+    draws: Vec<(f32, Draw<'a>)>,
+}
+
+impl<'a> TransparentQueue<'a> {
+    /// `camera_position` is usually `Camera::get_position`.
+    pub fn new(camera_position: &[f32; 3]) -> Self {
+        Self {
+            camera_position: *camera_position,
+            blend: Blend::alpha(),
+            draws: Default::default(),
+        }
+    }
+
+    pub fn with_blend(mut self, blend: Blend) -> Self {
+        self.blend = blend;
+        self
+    }
+
+    /// `position` is where the draw is sorted from, usually the centre of the object.
+    pub fn push<F>(&mut self, position: &[f32; 3], draw: F)
+    where
+        F: FnOnce() -> anyhow::Result<()> + 'a,
+    {
+        let distance_squared = position
+            .iter()
+            .zip(&self.camera_position)
+            .map(|(a, b)| (a - b) * (a - b))
+            .sum();
+        self.draws.push((distance_squared, Box::new(draw)));
+    }
+
+    pub fn len(&self) -> usize {
+        self.draws.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.draws.is_empty()
+    }
+
+    /// Draws back to front with blending on and depth writes off, then restores `base`.
+    /// Opaque geometry must already have been drawn so it occludes the queued draws.
+    pub fn draw(
+        mut self,
+        render_state_cache: &mut RenderStateCache,
+        base: &RenderState,
+    ) -> anyhow::Result<()> {
+        self.draws.sort_by(|(a, _), (b, _)| b.total_cmp(a));
+
+        render_state_cache.apply(&RenderState {
+            depth_test: true,
+            depth_write: false,
+            blend: Some(self.blend),
+            ..*base
+        })?;
+        for (_, draw) in self.draws {
+            draw()?;
+        }
+
+        render_state_cache.apply(base)?;
+        Ok(())
+    }
+}
+
+/// Weighted blended order-independent transparency (McGuire & Bavoil 2013).
+///
+/// Opaque geometry is drawn into an off-screen target first, then transparent surfaces in any
+/// order with a shader that writes two outputs:
+///
+/// ```glsl
+/// layout (location=0) out vec4 accumulation;
+/// layout (location=1) out float weight;
+///
+/// float w = clamp(0.03 / (1e-5 + pow(gl_FragCoord.z, 4.0)), 1e-2, 3e3);
+/// accumulation = vec4(colour.rgb * colour.a * w, colour.a);
+/// weight = colour.a * w;
+/// ```
+///
+/// Finally [`Self::composite`] resolves the
+/// result into the window.
+pub struct WeightedBlendedOit {
+    frame_buffer: FrameBuffer,
+    accumulation: TextureImage2D,
+    weight: TextureImage2D,
+    opaque: TextureImage2D,
+    depth_stencil: RenderBuffer,
+    composite_program: ShaderProgram,
+    quad: Mesh,
+}
+
+impl WeightedBlendedOit {
+    pub fn new(width: u32, height: u32) -> anyhow::Result<Self> {
+        let oit = Self {
+            frame_buffer: FrameBuffer::new(),
+            accumulation: TextureImage2D::new_empty(width, height, TextureFormat::RGBA16F)?,
+            weight: TextureImage2D::new_empty(width, height, TextureFormat::R16F)?,
+            opaque: TextureImage2D::new_empty(width, height, TextureFormat::RGBA)?,
+            depth_stencil: RenderBuffer::new(
+                width,
+                height,
+                RenderBufferStorageFormat::Depth24Stencil8,
+            )?,
+            composite_program: ShaderProgram::new(&[
+                Shader::new(include_str!("oit_composite.vert"), ShaderType::Vertex)?,
+                Shader::new(include_str!("oit_composite.frag"), ShaderType::Fragment)?,
+            ])?,
+            quad: [
+                [-1.0f32, -1.0],
+                [1.0, -1.0],
+                [1.0, 1.0],
+                [-1.0, -1.0],
+                [1.0, 1.0],
+                [-1.0, 1.0],
+            ]
+            .as_slice()
+            .try_into()?,
+        };
+
+        oit.frame_buffer.bind()?;
+        oit.accumulation.attach_to_frame_buffer(
+            FrameBufferTarget::All,
+            FrameBufferAttachment::Colour(ACCUMULATION_ATTACHMENT),
+        )?;
+        oit.weight.attach_to_frame_buffer(
+            FrameBufferTarget::All,
+            FrameBufferAttachment::Colour(WEIGHT_ATTACHMENT),
+        )?;
+        oit.opaque.attach_to_frame_buffer(
+            FrameBufferTarget::All,
+            FrameBufferAttachment::Colour(OPAQUE_ATTACHMENT),
+        )?;
+        oit.depth_stencil
+            .attach_to_frame_buffer(FrameBufferTarget::All, FrameBufferAttachment::DepthStencil)?;
+        oit.frame_buffer.check_complete()?;
+        FrameBuffer::bind_default()?;
+
+        Ok(oit)
+    }
+
+    /// Call whenever the window changes size, a no-op if it hasn't.
+    pub fn resize(&mut self, width: u32, height: u32) -> anyhow::Result<()> {
+        if self.opaque.size() == (width, height) {
+            return Ok(());
+        }
+
+        self.accumulation.resize(width, height)?;
+        self.weight.resize(width, height)?;
+        self.opaque.resize(width, height)?;
+        self.depth_stencil.resize(width, height)?;
+        Ok(())
+    }
+
+    /// Binds and clears the opaque target, draw opaque geometry after this.
+    pub fn begin_opaque(
+        &self,
+        render_state_cache: &mut RenderStateCache,
+        base: &RenderState,
+    ) -> anyhow::Result<()> {
+        self.frame_buffer.set_draw_buffers(&[OPAQUE_ATTACHMENT])?;
+        // Clears are affected by the depth write mask
+        render_state_cache.apply(base)?;
+        opengl_sys::clear(opengl_sys::BufferBit::Colour)?;
+        opengl_sys::clear(opengl_sys::BufferBit::Depth)?;
+        opengl_sys::clear(opengl_sys::BufferBit::Stencil)?;
+        Ok(())
+    }
+
+    /// Switches to the accumulation targets, draw transparent geometry in any order after this.
+    pub fn begin_transparent(
+        &self,
+        render_state_cache: &mut RenderStateCache,
+        base: &RenderState,
+    ) -> anyhow::Result<()> {
+        self.frame_buffer
+            .set_draw_buffers(&[ACCUMULATION_ATTACHMENT, WEIGHT_ATTACHMENT])?;
+        opengl_sys::clear_draw_buffer_colour(0, &[0.0, 0.0, 0.0, 1.0])?;
+        opengl_sys::clear_draw_buffer_colour(1, &[0.0; 4])?;
+
+        // Colour and weight are summed, while alpha keeps the product of (1 - alpha). Using one
+        // function for both targets keeps this within GL 3.3, which lacks per-target blending.
+        render_state_cache.apply(&RenderState {
+            depth_test: true,
+            depth_write: false,
+            blend: Some(Blend {
+                source: BlendFactor::One,
+                destination: BlendFactor::One,
+                source_alpha: BlendFactor::Zero,
+                destination_alpha: BlendFactor::OneMinusSourceAlpha,
+                equation: BlendEquation::Add,
+            }),
+            cull_face: None,
+            ..*base
+        })?;
+        Ok(())
+    }
+
+    /// Blends the accumulated transparency over the opaque target into the window, then
+    /// restores `base`.
+    pub fn composite(
+        &self,
+        render_state_cache: &mut RenderStateCache,
+        base: &RenderState,
+    ) -> anyhow::Result<()> {
+        FrameBuffer::bind_default()?;
+        render_state_cache.apply(&RenderState {
+            depth_test: false,
+            blend: None,
+            cull_face: None,
+            stencil: None,
+            ..*base
+        })?;
+
+        self.composite_program.enable()?;
+        TextureBindings::new(&self.composite_program)
+            .with("opaque", &self.opaque)?
+            .with("accumulation", &self.accumulation)?
+            .with("weight", &self.weight)?
+            .bind()?;
+        self.quad.draw(DrawMode::Triangles)?;
+
+        render_state_cache.apply(base)?;
+        Ok(())
+    }
+}
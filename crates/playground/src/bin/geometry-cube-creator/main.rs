@@ -13,7 +13,7 @@ fn main() -> anyhow::Result<()> {
         opengl::Shader::new(include_str!("shader.frag"), opengl::ShaderType::Fragment)?,
     ])?;
 
-    let shader_texture = opengl::ShaderProgramTexture::new(&texture, &shader_program, "tex", 0)?;
+    let shader_texture = opengl::ShaderProgramTexture::new(&texture, &shader_program, "tex")?;
 
     let mesh: opengl::Mesh = ([
         [0.0f32, 0.0, 0.0],
@@ -28,7 +28,6 @@ fn main() -> anyhow::Result<()> {
     .try_into()?;
 
     let mut camera = camera::Camera::new();
-    let mut camera_controls = camera::Controls::default();
 
     camera.set_position(&[0.0, 0.0, 3.0]);
 
@@ -36,28 +35,23 @@ fn main() -> anyhow::Result<()> {
     let view_location = shader_program.locate_uniform("view")?;
     let projection_location = shader_program.locate_uniform("projection")?;
 
-    window.run(|window_size, (_, seconds_since_last_frame), events| {
-        camera::process_events(
-            &mut camera,
-            &mut camera_controls,
-            70.0,
-            0.97,
-            seconds_since_last_frame,
-            events,
-        );
+    window.run(
+        |window_size, (_, seconds_since_last_frame), events, input| {
+            camera::process_events(&mut camera, seconds_since_last_frame, events, input);
 
-        shader_program.enable().unwrap();
-        shader_texture.draw().unwrap();
+            shader_program.enable().unwrap();
+            shader_texture.draw().unwrap();
 
-        set_mat4(model_location, &glm::one());
-        set_mat4(view_location, &camera.calculate_view());
-        set_mat4(
-            projection_location,
-            &camera.calculate_projection(window_size),
-        );
+            set_mat4(model_location, &glm::one());
+            set_mat4(view_location, &camera.calculate_view());
+            set_mat4(
+                projection_location,
+                &camera.calculate_projection(window_size),
+            );
 
-        mesh.draw(opengl::DrawMode::Points).unwrap();
-    })
+            mesh.draw(opengl::DrawMode::Points).unwrap();
+        },
+    )
 }
 
 fn set_mat4(location: opengl::UniformLocation, mat4: &glm::Mat4) {
@@ -29,9 +29,9 @@ fn main() -> anyhow::Result<()> {
     })?;
 
     let cube_shader_texture =
-        opengl::ShaderProgramTexture::new(&cube_texture, &cube_shader_program, "tex", 0)?;
+        opengl::ShaderProgramTexture::new(&cube_texture, &cube_shader_program, "tex")?;
     let skybox_shader_texture =
-        opengl::ShaderProgramTexture::new(&skybox_texture, &skybox_shader_program, "skybox", 0)?;
+        opengl::ShaderProgramTexture::new(&skybox_texture, &skybox_shader_program, "skybox")?;
 
     #[rustfmt::skip]
     let cube_vertices = [
@@ -131,7 +131,7 @@ fn main() -> anyhow::Result<()> {
             .collect();
 
     let skybox_mesh: opengl::Mesh = skybox_vertices.as_slice().try_into()?;
-    let cube_mesh = opengl::Mesh::new(
+    let mut cube_mesh = opengl::Mesh::new(
         &cube_vertices,
         &[(1, opengl::BufferAttributeSize::Triple).into()],
         None as Option<&[()]>,
@@ -159,6 +159,11 @@ fn main() -> anyhow::Result<()> {
             ],
         )),
     )?;
+    cube_mesh.set_bounds(opengl::Bounds::from_points(cube_vertices));
+
+    // Cubes only spin around their own centre, so the sphere around them covers every angle
+    let cube_radius = cube_mesh.bounds().map_or(1.0, |bounds| bounds.radius());
+    let mut visible_cubes = Vec::with_capacity(cube_offests.len());
 
     let cube_view_location = cube_shader_program.locate_uniform("view")?;
     let cube_projection_location = cube_shader_program.locate_uniform("projection")?;
@@ -168,49 +173,71 @@ fn main() -> anyhow::Result<()> {
     let skybox_view_location = skybox_shader_program.locate_uniform("view")?;
     let skybox_projection_location = skybox_shader_program.locate_uniform("projection")?;
 
+    let cube_render_state = opengl::RenderState {
+        depth_test: true,
+        ..Default::default()
+    };
+    let skybox_render_state = opengl::RenderState {
+        depth_func: opengl::DepthFunc::LessEqual,
+        ..cube_render_state
+    };
+    let mut render_state_cache = opengl::RenderStateCache::new()?;
+
     let mut camera = camera::Camera::new();
-    let mut camera_controls = camera::Controls::default();
 
     camera.set_position(&[0.0, 0.0, 3.0]);
+    camera.set_movement(camera::Movement {
+        acceleration: 90.0,
+        max_speed: 50.0,
+        ..Default::default()
+    });
 
     let mut min_fps = 100.0f32;
     window.run(
-        |window_size, (total_seconds_passed, seconds_since_last_frame), events| {
-            camera::process_events(
-                &mut camera,
-                &mut camera_controls,
-                90.0,
-                0.97,
-                seconds_since_last_frame,
-                events,
-            );
+        |window_size, (total_seconds_passed, seconds_since_last_frame), events, input| {
+            camera::process_events(&mut camera, seconds_since_last_frame, events, input);
 
             let camera_view = camera.calculate_view();
             let camera_projection = camera.calculate_projection(window_size);
+            let orbit_angle = (20.0 * total_seconds_passed).to_radians();
+
+            let frustum = camera.calculate_frustum(window_size);
+            visible_cubes.clear();
+            visible_cubes.extend(
+                cube_offests
+                    .iter()
+                    .filter(|(position, _, orbit_axis)| {
+                        // Same orbit as cube.vert applies
+                        let center = glm::rotate_vec3(
+                            &glm::make_vec3(position),
+                            -orbit_angle,
+                            &glm::normalize(&glm::make_vec3(orbit_axis)),
+                        );
+                        frustum.intersects_sphere(&center.into(), cube_radius)
+                    })
+                    .copied(),
+            );
+            cube_mesh.set_instances(&visible_cubes).unwrap();
 
+            render_state_cache.apply(&cube_render_state).unwrap();
             cube_shader_program.enable().unwrap();
             opengl_sys::set_uniform_f32(
                 cube_rotate_angle,
                 (50.0 * total_seconds_passed).to_radians(),
             )
             .unwrap();
-            opengl_sys::set_uniform_f32(
-                cube_orbit_angle,
-                (20.0 * total_seconds_passed).to_radians(),
-            )
-            .unwrap();
+            opengl_sys::set_uniform_f32(cube_orbit_angle, orbit_angle).unwrap();
             set_mat4(cube_view_location, &camera_view);
             set_mat4(cube_projection_location, &camera_projection);
             cube_shader_texture.draw().unwrap();
             cube_mesh.draw(opengl::DrawMode::Triangles).unwrap();
 
+            render_state_cache.apply(&skybox_render_state).unwrap();
             skybox_shader_program.enable().unwrap();
             set_mat4(skybox_view_location, &camera_view);
             set_mat4(skybox_projection_location, &camera_projection);
             skybox_shader_texture.draw().unwrap();
-            opengl_sys::set_depth_func(opengl_sys::DepthFunc::LessEqual);
             skybox_mesh.draw(opengl::DrawMode::Triangles).unwrap();
-            opengl_sys::set_depth_func(opengl_sys::DepthFunc::Less);
 
             let current_fps = 1.0 / seconds_since_last_frame;
             if total_seconds_passed > 2.0 {
@@ -8,7 +8,7 @@ fn main() -> anyhow::Result<()> {
 
     let mesh: opengl::Mesh = ([[-0.5, -0.5], [0.0, 0.5], [0.5, -0.5f32]].as_slice()).try_into()?;
 
-    window.run(|_, _, _| {
+    window.run(|_, _, _, _| {
         shader_program.enable().unwrap();
         mesh.draw(opengl::DrawMode::Triangles).unwrap();
     })
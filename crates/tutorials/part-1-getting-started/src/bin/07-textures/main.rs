@@ -10,11 +10,11 @@ fn main() -> anyhow::Result<()> {
         opengl::Shader::new(include_str!("shader.frag"), opengl::ShaderType::Fragment)?,
     ])?;
 
-    let shader_texture = opengl::ShaderProgramTexture::new(&texture, &shader_program, "tex", 0)?;
+    let shader_texture = opengl::ShaderProgramTexture::new(&texture, &shader_program, "tex")?;
 
     let mesh: opengl::Mesh = ([[-0.5, -0.5], [0.0, 0.5], [0.5, -0.5f32]].as_slice()).try_into()?;
 
-    window.run(|_, _, _| {
+    window.run(|_, _, _, _| {
         shader_program.enable().unwrap();
         shader_texture.draw().unwrap();
         mesh.draw(opengl::DrawMode::Triangles).unwrap();
@@ -12,7 +12,7 @@ fn main() -> anyhow::Result<()> {
         opengl::Shader::new(include_str!("shader.frag"), opengl::ShaderType::Fragment)?,
     ])?;
 
-    let shader_texture = opengl::ShaderProgramTexture::new(&texture, &shader_program, "tex", 0)?;
+    let shader_texture = opengl::ShaderProgramTexture::new(&texture, &shader_program, "tex")?;
 
     #[rustfmt::skip]
     let vertices = [
@@ -62,7 +62,6 @@ fn main() -> anyhow::Result<()> {
     let mesh: opengl::Mesh = vertices.as_slice().try_into()?;
 
     let mut camera = camera::Camera::new();
-    let mut camera_controls = camera::Controls::default();
 
     camera.set_position(&[0.0, 0.0, 3.0]);
 
@@ -70,38 +69,33 @@ fn main() -> anyhow::Result<()> {
     let view_location = shader_program.locate_uniform("view")?;
     let projection_location = shader_program.locate_uniform("projection")?;
 
-    window.run(|window_size, (_, seconds_since_last_frame), events| {
-        camera::process_events(
-            &mut camera,
-            &mut camera_controls,
-            70.0,
-            0.97,
-            seconds_since_last_frame,
-            events,
-        );
-
-        shader_program.enable().unwrap();
-        shader_texture.draw().unwrap();
-
-        opengl_sys::set_uniform_mat4(
-            model_location,
-            false,
-            glm::value_ptr::<f32, 4, 4>(&glm::one()),
-        )
-        .unwrap();
-        opengl_sys::set_uniform_mat4(
-            view_location,
-            false,
-            glm::value_ptr(&camera.calculate_view()),
-        )
-        .unwrap();
-        opengl_sys::set_uniform_mat4(
-            projection_location,
-            false,
-            glm::value_ptr(&camera.calculate_projection(window_size)),
-        )
-        .unwrap();
-
-        mesh.draw(opengl::DrawMode::Triangles).unwrap();
-    })
+    window.run(
+        |window_size, (_, seconds_since_last_frame), events, input| {
+            camera::process_events(&mut camera, seconds_since_last_frame, events, input);
+
+            shader_program.enable().unwrap();
+            shader_texture.draw().unwrap();
+
+            opengl_sys::set_uniform_mat4(
+                model_location,
+                false,
+                glm::value_ptr::<f32, 4, 4>(&glm::one()),
+            )
+            .unwrap();
+            opengl_sys::set_uniform_mat4(
+                view_location,
+                false,
+                glm::value_ptr(&camera.calculate_view()),
+            )
+            .unwrap();
+            opengl_sys::set_uniform_mat4(
+                projection_location,
+                false,
+                glm::value_ptr(&camera.calculate_projection(window_size)),
+            )
+            .unwrap();
+
+            mesh.draw(opengl::DrawMode::Triangles).unwrap();
+        },
+    )
 }
@@ -67,7 +67,6 @@ fn main() -> anyhow::Result<()> {
     let mesh: opengl::Mesh = vertices.as_slice().try_into()?;
 
     let mut camera = camera::Camera::new();
-    let mut camera_controls = camera::Controls::default();
 
     camera.set_position(&[1.5, 1.8, 5.0]);
 
@@ -103,15 +102,8 @@ fn main() -> anyhow::Result<()> {
     let light_light_colour_location = light_shader_program.locate_uniform("light_colour")?;
 
     window.run(
-        |window_size, (total_seconds_passed, seconds_since_last_frame), events| {
-            camera::process_events(
-                &mut camera,
-                &mut camera_controls,
-                70.0,
-                0.97,
-                seconds_since_last_frame,
-                events,
-            );
+        |window_size, (total_seconds_passed, seconds_since_last_frame), events, input| {
+            camera::process_events(&mut camera, seconds_since_last_frame, events, input);
 
             let light_colour_t = total_seconds_passed / 5.0;
             let light_colour = glm::vec3(
@@ -24,13 +24,11 @@ fn main() -> anyhow::Result<()> {
         &container_texture,
         &object_shader_program,
         "material.diffuse",
-        0,
     )?;
     let container_specular_shader_texture = opengl::ShaderProgramTexture::new(
         &container_specular_texture,
         &object_shader_program,
         "material.specular",
-        1,
     )?;
 
     #[rustfmt::skip]
@@ -100,7 +98,6 @@ fn main() -> anyhow::Result<()> {
     let mesh: opengl::Mesh = vertices.as_slice().try_into()?;
 
     let mut camera = camera::Camera::new();
-    let mut camera_controls = camera::Controls::default();
 
     camera.set_position(&[0.0, 0.0, 3.0]);
     let light_pos = glm::vec3(1.2, 1.0, 2.0);
@@ -134,53 +131,48 @@ fn main() -> anyhow::Result<()> {
     let light_view_location = light_shader_program.locate_uniform("view")?;
     let light_projection_location = light_shader_program.locate_uniform("projection")?;
 
-    window.run(|window_size, (_, seconds_since_last_frame), events| {
-        camera::process_events(
-            &mut camera,
-            &mut camera_controls,
-            70.0,
-            0.97,
-            seconds_since_last_frame,
-            events,
-        );
-
-        object_shader_program.enable().unwrap();
-        set_mat4(object_view_location, &camera.calculate_view());
-        opengl_sys::set_uniform_vec3(object_view_pos_location, &camera.get_position()).unwrap();
-        set_mat4(
-            object_projection_location,
-            &camera.calculate_projection(window_size),
-        );
-
-        container_shader_texture.draw().unwrap();
-        container_specular_shader_texture.draw().unwrap();
-        for (index, [x, y, z]) in object_positions.iter().enumerate() {
+    window.run(
+        |window_size, (_, seconds_since_last_frame), events, input| {
+            camera::process_events(&mut camera, seconds_since_last_frame, events, input);
+
+            object_shader_program.enable().unwrap();
+            set_mat4(object_view_location, &camera.calculate_view());
+            opengl_sys::set_uniform_vec3(object_view_pos_location, &camera.get_position()).unwrap();
+            set_mat4(
+                object_projection_location,
+                &camera.calculate_projection(window_size),
+            );
+
+            container_shader_texture.draw().unwrap();
+            container_specular_shader_texture.draw().unwrap();
+            for (index, [x, y, z]) in object_positions.iter().enumerate() {
+                set_mat4(
+                    object_model_location,
+                    &glm::rotate(
+                        &glm::translate(&glm::one(), &glm::vec3(*x, *y, *z)),
+                        (20.0 * index as f32).to_radians(),
+                        &glm::vec3(1.0, 0.3, 0.5),
+                    ),
+                );
+                mesh.draw(opengl::DrawMode::Triangles).unwrap();
+            }
+
+            light_shader_program.enable().unwrap();
             set_mat4(
-                object_model_location,
-                &glm::rotate(
-                    &glm::translate(&glm::one(), &glm::vec3(*x, *y, *z)),
-                    (20.0 * index as f32).to_radians(),
-                    &glm::vec3(1.0, 0.3, 0.5),
+                light_model_location,
+                &glm::scale(
+                    &glm::translate(&glm::one(), &light_pos),
+                    &glm::vec3(0.2, 0.2, 0.2),
                 ),
             );
+            set_mat4(light_view_location, &camera.calculate_view());
+            set_mat4(
+                light_projection_location,
+                &camera.calculate_projection(window_size),
+            );
             mesh.draw(opengl::DrawMode::Triangles).unwrap();
-        }
-
-        light_shader_program.enable().unwrap();
-        set_mat4(
-            light_model_location,
-            &glm::scale(
-                &glm::translate(&glm::one(), &light_pos),
-                &glm::vec3(0.2, 0.2, 0.2),
-            ),
-        );
-        set_mat4(light_view_location, &camera.calculate_view());
-        set_mat4(
-            light_projection_location,
-            &camera.calculate_projection(window_size),
-        );
-        mesh.draw(opengl::DrawMode::Triangles).unwrap();
-    })
+        },
+    )
 }
 
 fn set_mat4(location: opengl::UniformLocation, mat4: &glm::Mat4) {
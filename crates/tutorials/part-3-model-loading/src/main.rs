@@ -1,7 +1,38 @@
 use nalgebra_glm as glm;
-
+use std::path::PathBuf;
+
+const UPLOAD_BUDGET: std::time::Duration = std::time::Duration::from_millis(4);
+const ANIMATION_BLEND_SECONDS: f32 = 0.3;
+/// Playback advances by a fixed step per frame, so every run renders the same frames.
+const PLAYBACK_STEP: f32 = 1.0 / 60.0;
+
+/// Press C to switch between orbiting, arcball rotation and flying around the model. Rigged
+/// models loop their first animation, N blends into the next one.
+///
+/// `--record <file>` saves the camera path when the window closes, `--play <file>` flies along
+/// a saved path once the model has loaded and reports how long it took.
 fn main() -> anyhow::Result<()> {
-    let window = winman::Window::new("21-model-loading", 1920, 1080)?;
+    let mut record_to = None;
+    let mut player = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match (arg.as_str(), args.next()) {
+            ("--record", Some(file)) => record_to = Some(PathBuf::from(file)),
+            ("--play", Some(file)) => {
+                let path = camera::CameraPath::load_from_file(&file)?;
+                if path.keyframes.is_empty() {
+                    anyhow::bail!("{file} has no keyframes");
+                }
+                player = Some(camera::PathPlayer::new(path));
+            }
+            _ => anyhow::bail!("Usage: [--record <file>] [--play <file>]"),
+        }
+    }
+    let mut recorder = record_to.as_ref().map(|_| camera::PathRecorder::new(0.0));
+
+    let window = winman::WindowBuilder::new("21-model-loading", 1920, 1080)
+        .cursor_mode(winman::CursorMode::Visible)
+        .build()?;
 
     let shader_program = opengl::ShaderProgram::new(&[
         opengl::Shader::new(include_str!("shader.vert"), opengl::ShaderType::Vertex)?,
@@ -13,14 +44,23 @@ fn main() -> anyhow::Result<()> {
     let objects_dir = asset_dir.join("objects");
     let backpack_dir = objects_dir.join("backpack");
 
-    let model = Model::load_from_file(backpack_dir.join("backpack.obj"))?;
+    let num_workers = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let asset_loader = opengl::AssetLoader::new(num_workers);
+    let mut model =
+        opengl::Model::stream_from_file(&asset_loader, backpack_dir.join("backpack.obj"))?;
 
-    let mut camera = camera::Camera::new();
-    let mut camera_controls = camera::Controls::default();
+    let mut fly_camera = camera::Camera::new();
+    fly_camera.set_position(&[0.0, 0.0, 3.0]);
+    let mut animation_player = opengl::AnimationPlayer::new();
 
-    camera.set_position(&[0.0, 0.0, 3.0]);
+    let mut controllers: [Box<dyn camera::CameraController>; 3] = [
+        Box::new(camera::OrbitController::new(&[0.0, 0.0, 0.0], 3.0)),
+        Box::new(camera::ArcballController::new(&[0.0, 0.0, 0.0], 3.0)),
+        Box::new(fly_camera),
+    ];
+    let mut active_controller = 0;
 
-    let model_location = shader_program.locate_uniform("model")?;
+    let model_uniforms = opengl::ModelUniforms::new(&shader_program)?;
     let view_location = shader_program.locate_uniform("view")?;
     let projection_location = shader_program.locate_uniform("projection")?;
     let view_pos_location = shader_program.locate_uniform("view_pos")?;
@@ -36,181 +76,112 @@ fn main() -> anyhow::Result<()> {
     opengl_sys::set_uniform_vec3(light_diffuse_location, &[0.5, 0.5, 0.5])?;
     opengl_sys::set_uniform_vec3(light_specular_location, &[1.0, 1.0, 1.0])?;
 
-    window.run(|window_size, (_, seconds_since_last_frame), events| {
-        camera::process_events(
-            &mut camera,
-            &mut camera_controls,
-            70.0,
-            0.97,
-            seconds_since_last_frame,
-            events,
-        );
-
-        shader_program.enable().unwrap();
-        opengl_sys::set_uniform_mat4(
-            model_location,
-            false,
-            glm::value_ptr::<f32, 4, 4>(&glm::one()),
-        )
-        .unwrap();
-        opengl_sys::set_uniform_mat4(
-            view_location,
-            false,
-            glm::value_ptr(&camera.calculate_view()),
-        )
-        .unwrap();
-        opengl_sys::set_uniform_mat4(
-            projection_location,
-            false,
-            glm::value_ptr(&camera.calculate_projection(window_size)),
-        )
-        .unwrap();
-        opengl_sys::set_uniform_vec3(view_pos_location, &camera.get_position()).unwrap();
-
-        model.draw(&shader_program).unwrap();
-    })
-}
-
-struct TexturedMesh {
-    diffuse: opengl::TextureImage2D,
-    specular: opengl::TextureImage2D,
-    meshes: Vec<opengl::Mesh>,
-}
-
-impl TexturedMesh {
-    fn draw(&self, shader: &opengl::ShaderProgram) -> anyhow::Result<()> {
-        let shader_diffuse_texture =
-            opengl::ShaderProgramTexture::new(&self.diffuse, &shader, "material.diffuse", 0)?;
-        let shader_specular_texture =
-            opengl::ShaderProgramTexture::new(&self.specular, &shader, "material.specular", 1)?;
-
-        shader_diffuse_texture.draw()?;
-        shader_specular_texture.draw()?;
-
-        for mesh in &self.meshes {
-            mesh.draw(opengl::DrawMode::Triangles)?;
-        }
-
-        Ok(())
-    }
-}
-
-struct Model {
-    textured_meshes: Vec<Option<TexturedMesh>>,
-}
-
-impl Model {
-    fn load_from_file<P: AsRef<std::path::Path>>(path: P) -> anyhow::Result<Self> {
-        let scene = russimp::scene::Scene::from_file(
-            &path.as_ref().to_str().unwrap(),
-            vec![
-                russimp::scene::PostProcess::FlipUVs,
-                russimp::scene::PostProcess::Triangulate,
-            ],
-        )?;
-
-        let parent_path = path.as_ref().parent().unwrap();
-
-        let mut textured_meshes: Vec<_> = Default::default();
-        for material in scene.materials {
-            let diffuse = Self::load_material(
-                parent_path,
-                &material.properties,
-                russimp::material::TextureType::Diffuse,
-            )?;
-
-            let specular = Self::load_material(
-                parent_path,
-                &material.properties,
-                russimp::material::TextureType::Specular,
-            )?;
-
-            if diffuse.is_none() || specular.is_none() {
-                textured_meshes.push(None);
-                continue;
+    let mut playback_frames = 0u32;
+    let mut playback_start = None;
+    let mut load_error = None;
+
+    window.run(
+        |window_size, (_, seconds_since_last_frame), events, input| {
+            match model.update(&asset_loader, UPLOAD_BUDGET) {
+                Ok(texture_errors) => {
+                    for error in texture_errors {
+                        eprintln!("Using a placeholder texture: {error:#}");
+                    }
+                }
+                Err(error) => {
+                    load_error = Some(error);
+                    return winman::ControlFlow::Exit;
+                }
             }
 
-            textured_meshes.push(Some(TexturedMesh {
-                diffuse: diffuse.unwrap(),
-                specular: specular.unwrap(),
-                meshes: Default::default(),
-            }))
-        }
-
-        for mesh in &scene.meshes {
-            let mut indices: Vec<u32> = Default::default();
-
-            for face in &mesh.faces {
-                indices.append(&mut face.0.clone());
+            let mut next_animation = false;
+            for event in events {
+                match event {
+                    winman::Event::KeyUp {
+                        keycode: winman::Keycode::C,
+                        ..
+                    } => active_controller = (active_controller + 1) % controllers.len(),
+                    winman::Event::KeyUp {
+                        keycode: winman::Keycode::N,
+                        ..
+                    } => next_animation = true,
+                    _ => {}
+                }
             }
 
-            let uv_chan = mesh.texture_coords[0].as_ref().unwrap();
-
-            let vertices: Vec<_> = itertools::izip!(&mesh.vertices, &mesh.normals, uv_chan)
-                .map(|(position, normal, uv)| {
-                    [
-                        position.x, position.y, position.z, normal.x, normal.y, normal.z, uv.x,
-                        uv.y,
-                    ]
-                })
-                .collect();
-
-            let index = mesh.material_index as usize;
-            if textured_meshes.len() <= index {
-                anyhow::bail!(format!("invalid material index: {index}"));
+            // Playback steps the animation along with the camera, so every run renders the same
+            // frames
+            let animation_step = match &player {
+                Some(_) if !model.is_loaded() => 0.0,
+                Some(_) => PLAYBACK_STEP,
+                None => seconds_since_last_frame,
+            };
+            if let Some(scene) = model.scene_mut() {
+                let clip_count = scene.animations.len();
+                match animation_player.clip() {
+                    _ if clip_count == 0 => {}
+                    None => animation_player.play(0, true),
+                    Some(clip) if next_animation => animation_player.cross_fade(
+                        (clip + 1) % clip_count,
+                        true,
+                        ANIMATION_BLEND_SECONDS,
+                    ),
+                    Some(_) => {}
+                }
+                animation_player.advance(&scene.animations, animation_step);
+                animation_player.apply(&scene.animations, &mut scene.graph);
             }
-
-            let mesh = opengl::Mesh::new(
-                &vertices,
-                &[
-                    (0, opengl::BufferAttributeSize::Triple).into(),
-                    (1, opengl::BufferAttributeSize::Triple).into(),
-                    (2, opengl::BufferAttributeSize::Double).into(),
-                ],
-                Some(&indices),
-                None as Option<(&[()], &[opengl::BufferAttribute])>,
-            )?;
-            if let Some(textured_mesh) = &mut textured_meshes[index] {
-                textured_mesh.meshes.push(mesh);
+            let camera = &mut controllers[active_controller];
+            let (view, position) = match &mut player {
+                // Wait at the start until everything has streamed in
+                Some(player) if !model.is_loaded() => {
+                    let start = player.path().sample(0.0).unwrap();
+                    (start.calculate_view(), start.position)
+                }
+                Some(player) => {
+                    playback_start.get_or_insert_with(std::time::Instant::now);
+                    let Some(pose) = player.advance(PLAYBACK_STEP) else {
+                        return winman::ControlFlow::Exit;
+                    };
+                    playback_frames += 1;
+                    (pose.calculate_view(), pose.position)
+                }
+                None => {
+                    camera.process_events(window_size, seconds_since_last_frame, events, input);
+                    (camera.calculate_view(), camera.get_position())
+                }
+            };
+            if let Some(recorder) = &mut recorder {
+                recorder.record(&view, seconds_since_last_frame);
             }
-        }
 
-        Ok(Model { textured_meshes })
+            shader_program.enable().unwrap();
+            opengl_sys::set_uniform_mat4(view_location, false, glm::value_ptr(&view)).unwrap();
+            opengl_sys::set_uniform_mat4(
+                projection_location,
+                false,
+                glm::value_ptr(&camera.calculate_projection(window_size)),
+            )
+            .unwrap();
+            opengl_sys::set_uniform_vec3(view_pos_location, &position).unwrap();
+
+            model.draw(&model_uniforms, &glm::identity()).unwrap();
+            winman::ControlFlow::Continue
+        },
+    )?;
+    if let Some(error) = load_error {
+        return Err(error);
     }
 
-    fn load_material<P: AsRef<std::path::Path>>(
-        parent_path: P,
-        properties: &[russimp::material::MaterialProperty],
-        target: russimp::material::TextureType,
-    ) -> anyhow::Result<Option<opengl::TextureImage2D>> {
-        let property = properties
-            .iter()
-            .find(|property| property.key == "$tex.file" && property.semantic == target);
-
-        if property.is_none() {
-            return Ok(None);
-        }
-
-        let property = property.unwrap();
-
-        let texture = if let russimp::material::PropertyTypeInfo::String(filename) = &property.data
-        {
-            Some(opengl::TextureImage2D::load_from_file(
-                &parent_path.as_ref().join(filename),
-            )?)
-        } else {
-            None
-        };
-
-        Ok(texture)
+    if let Some(start) = playback_start {
+        let seconds = start.elapsed().as_secs_f32();
+        println!(
+            "Played {playback_frames} frames in {seconds:.2}s, {:.2}ms per frame",
+            1000.0 * seconds / playback_frames.max(1) as f32
+        );
     }
-
-    fn draw(&self, shader: &opengl::ShaderProgram) -> anyhow::Result<()> {
-        for mesh in &self.textured_meshes {
-            if let Some(mesh) = mesh {
-                mesh.draw(&shader).unwrap();
-            }
-        }
-        Ok(())
+    if let (Some(recorder), Some(file)) = (recorder, record_to) {
+        recorder.finish().save_to_file(file)?;
     }
+    Ok(())
 }
@@ -1,21 +1,35 @@
-
 #version 330 core
 layout (location = 0) in vec3 position;
 layout (location = 1) in vec3 normal;
 layout (location = 2) in vec2 texture_coordinates;
+layout (location = 3) in vec4 bone_indices;
+layout (location = 4) in vec4 bone_weights;
 
 out vec3 frag_normal;
 out vec2 frag_texture_coordinates;
 out vec3 frag_word_position;
 
+const int MAX_BONES = 100;
+
 uniform mat4 model;
 uniform mat4 view;
 uniform mat4 projection;
+uniform mat4 bones[MAX_BONES];
+uniform bool skinned;
 
 void main()
 {
-    frag_normal = mat3(transpose(inverse(model))) * normal;
+    mat4 skin = mat4(1.0);
+    if (skinned && dot(bone_weights, vec4(1.0)) > 0.0) {
+        skin = bone_weights.x * bones[int(bone_indices.x)]
+            + bone_weights.y * bones[int(bone_indices.y)]
+            + bone_weights.z * bones[int(bone_indices.z)]
+            + bone_weights.w * bones[int(bone_indices.w)];
+    }
+    mat4 skinned_model = model * skin;
+
+    frag_normal = mat3(transpose(inverse(skinned_model))) * normal;
     frag_texture_coordinates = texture_coordinates;
-    frag_word_position = vec3(model * vec4(position, 1.0));
-    gl_Position = projection * view * model * vec4(position, 1.0);
+    frag_word_position = vec3(skinned_model * vec4(position, 1.0));
+    gl_Position = projection * view * skinned_model * vec4(position, 1.0);
 }
\ No newline at end of file
@@ -0,0 +1,191 @@
+use nalgebra_glm as glm;
+
+/// Click to select the cube under the crosshair, press P to switch between ray casting on the
+/// CPU and reading back an ID buffer.
+fn main() -> anyhow::Result<()> {
+    let window = winman::Window::new("4.23 Stencil Testing", 1920, 1080)?;
+
+    let asset_dir = std::env::current_dir()?.join("assets");
+    let image_dir = asset_dir.join("images");
+    let texture = opengl::TextureImage2D::load_from_file(&image_dir.join("container2.png"))?;
+
+    let shader_program = opengl::ShaderProgram::new(&[
+        opengl::Shader::new(include_str!("shader.vert"), opengl::ShaderType::Vertex)?,
+        opengl::Shader::new(include_str!("shader.frag"), opengl::ShaderType::Fragment)?,
+    ])?;
+
+    let pick_program = opengl::ShaderProgram::new(&[
+        opengl::Shader::new(include_str!("shader.vert"), opengl::ShaderType::Vertex)?,
+        opengl::Shader::new(include_str!("pick.frag"), opengl::ShaderType::Fragment)?,
+    ])?;
+
+    let shader_texture = opengl::ShaderProgramTexture::new(&texture, &shader_program, "tex")?;
+
+    #[rustfmt::skip]
+    let vertices = [
+        [-0.5f32, -0.5, 0.5],
+        [-0.5, 0.5, 0.5],
+        [0.5, 0.5, 0.5],
+        [-0.5, -0.5, 0.5],
+        [0.5, 0.5, 0.5],
+        [0.5, -0.5, 0.5],
+
+        [-0.5, -0.5, -0.5],
+        [-0.5, 0.5, -0.5],
+        [-0.5, 0.5, 0.5],
+        [-0.5, -0.5, -0.5],
+        [-0.5, 0.5, 0.5],
+        [-0.5, -0.5, 0.5],
+        
+        [-0.5, 0.5, 0.5],
+        [-0.5, 0.5, -0.5],
+        [0.5, 0.5, -0.5],
+        [-0.5, 0.5, 0.5],
+        [0.5, 0.5, -0.5],
+        [0.5, 0.5, 0.5],
+
+        [0.5, -0.5, 0.5],
+        [0.5, 0.5, 0.5],
+        [0.5, 0.5, -0.5],
+        [0.5, -0.5, 0.5],
+        [0.5, 0.5, -0.5],
+        [0.5, -0.5, -0.5],
+
+        [0.5, -0.5, 0.5],
+        [0.5, -0.5, -0.5],
+        [-0.5, -0.5, -0.5],
+        [0.5, -0.5, 0.5],
+        [-0.5, -0.5, -0.5],
+        [-0.5, -0.5, 0.5],
+
+        [0.5, -0.5, -0.5],
+        [0.5, 0.5, -0.5],
+        [-0.5, 0.5, -0.5],
+        [0.5, -0.5, -0.5],
+        [-0.5, 0.5, -0.5],
+        [-0.5, -0.5, -0.5],
+    ];
+
+    let mesh: opengl::Mesh = vertices.as_slice().try_into()?;
+
+    let outline = opengl::Outline::new(&[1.0, 0.6, 0.0], 1.05)?;
+    let render_state = opengl::RenderState {
+        depth_test: true,
+        ..Default::default()
+    };
+    let mut render_state_cache = opengl::RenderStateCache::new()?;
+    let mut picking = opengl::PickingTarget::new(1920, 1080)?;
+    let mut use_id_buffer = false;
+
+    let mut camera = camera::Camera::new();
+
+    camera.set_position(&[0.0, 0.0, 3.0]);
+
+    let model_location = shader_program.locate_uniform("model")?;
+    let view_location = shader_program.locate_uniform("view")?;
+    let projection_location = shader_program.locate_uniform("projection")?;
+    let pick_model_location = pick_program.locate_uniform("model")?;
+    let pick_view_location = pick_program.locate_uniform("view")?;
+    let pick_projection_location = pick_program.locate_uniform("projection")?;
+    let pick_id_location = pick_program.locate_uniform("object_id")?;
+
+    let models = [
+        glm::translation(&glm::vec3(-1.0, 0.0, -1.0)),
+        glm::translation(&glm::vec3(1.0, 0.0, 0.0)),
+        glm::translation(&glm::vec3(0.0, 1.5, -2.0)),
+    ];
+    let mut selected = Some(1);
+
+    window.run(
+        |window_size, (_, seconds_since_last_frame), events, input| {
+            camera::process_events(&mut camera, seconds_since_last_frame, events, input);
+
+            let view = camera.calculate_view();
+            let projection = camera.calculate_projection(window_size);
+
+            // The cursor is captured, so pick through the middle of the window
+            let crosshair = (window_size.0 as f32 / 2.0, window_size.1 as f32 / 2.0);
+            for event in events {
+                match event {
+                    winman::Event::KeyUp {
+                        keycode: winman::Keycode::P,
+                        ..
+                    } => use_id_buffer = !use_id_buffer,
+                    winman::Event::MouseButtonDown {
+                        button: winman::MouseButton::Left,
+                        ..
+                    } if use_id_buffer => {
+                        picking.resize(window_size.0, window_size.1).unwrap();
+                        picking
+                            .begin(&mut render_state_cache, &render_state)
+                            .unwrap();
+                        pick_program.enable().unwrap();
+                        set_mat4(pick_view_location, &view);
+                        set_mat4(pick_projection_location, &projection);
+                        for (id, model) in (1..).zip(&models) {
+                            opengl_sys::set_uniform_u32(pick_id_location, id).unwrap();
+                            set_mat4(pick_model_location, model);
+                            mesh.draw(opengl::DrawMode::Triangles).unwrap();
+                        }
+                        selected = picking
+                            .pick(crosshair.0, crosshair.1)
+                            .unwrap()
+                            .map(|id| id as usize - 1);
+                    }
+                    winman::Event::MouseButtonDown {
+                        button: winman::MouseButton::Left,
+                        ..
+                    } => {
+                        let ray = camera.screen_ray(crosshair, window_size);
+                        let bounds = mesh.bounds().unwrap();
+                        selected = models
+                            .iter()
+                            .enumerate()
+                            .filter_map(|(i, model)| {
+                                ray.transform(&glm::inverse(model))
+                                    .intersect_aabb(&bounds.min, &bounds.max)
+                                    .map(|distance| (distance, i))
+                            })
+                            .min_by(|(a, _), (b, _)| a.total_cmp(b))
+                            .map(|(_, i)| i);
+                    }
+                    _ => {}
+                }
+            }
+
+            render_state_cache.apply(&render_state).unwrap();
+            shader_program.enable().unwrap();
+            shader_texture.draw().unwrap();
+            set_mat4(view_location, &view);
+            set_mat4(projection_location, &projection);
+
+            for (i, model) in models.iter().enumerate() {
+                if selected != Some(i) {
+                    set_mat4(model_location, model);
+                    mesh.draw(opengl::DrawMode::Triangles).unwrap();
+                }
+            }
+
+            if let Some(selected_model) = selected.map(|i| &models[i]) {
+                outline
+                    .draw(
+                        &mut render_state_cache,
+                        &render_state,
+                        glm::value_ptr(&view),
+                        glm::value_ptr(&projection),
+                        &[(&mesh, glm::value_ptr(selected_model))],
+                        || {
+                            shader_program.enable()?;
+                            set_mat4(model_location, selected_model);
+                            mesh.draw(opengl::DrawMode::Triangles)
+                        },
+                    )
+                    .unwrap();
+            }
+        },
+    )
+}
+
+fn set_mat4(location: opengl::UniformLocation, mat4: &glm::Mat4) {
+    opengl_sys::set_uniform_mat4(location, false, glm::value_ptr(mat4)).unwrap();
+}
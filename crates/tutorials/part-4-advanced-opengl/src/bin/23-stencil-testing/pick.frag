@@ -0,0 +1,9 @@
+#version 330 core
+
+layout (location=0) out uint id;
+
+uniform uint object_id;
+
+void main() {
+    id = object_id;
+}
\ No newline at end of file
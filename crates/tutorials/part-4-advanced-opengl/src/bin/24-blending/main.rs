@@ -0,0 +1,208 @@
+use nalgebra_glm as glm;
+
+/// Press O to switch between sorted blending and weighted blended OIT.
+fn main() -> anyhow::Result<()> {
+    let window = winman::Window::new("4.24 Blending", 1920, 1080)?;
+
+    let asset_dir = std::env::current_dir()?.join("assets");
+    let image_dir = asset_dir.join("images");
+    let texture = opengl::TextureImage2D::load_from_file(&image_dir.join("container2.png"))?;
+
+    let shader_program = opengl::ShaderProgram::new(&[
+        opengl::Shader::new(include_str!("shader.vert"), opengl::ShaderType::Vertex)?,
+        opengl::Shader::new(include_str!("shader.frag"), opengl::ShaderType::Fragment)?,
+    ])?;
+    let window_program = WindowProgram::new(include_str!("window.frag"))?;
+    let window_oit_program = WindowProgram::new(include_str!("window_oit.frag"))?;
+
+    let shader_texture = opengl::ShaderProgramTexture::new(&texture, &shader_program, "tex")?;
+
+    #[rustfmt::skip]
+    let vertices = [
+        [-0.5f32, -0.5, 0.5],
+        [-0.5, 0.5, 0.5],
+        [0.5, 0.5, 0.5],
+        [-0.5, -0.5, 0.5],
+        [0.5, 0.5, 0.5],
+        [0.5, -0.5, 0.5],
+
+        [-0.5, -0.5, -0.5],
+        [-0.5, 0.5, -0.5],
+        [-0.5, 0.5, 0.5],
+        [-0.5, -0.5, -0.5],
+        [-0.5, 0.5, 0.5],
+        [-0.5, -0.5, 0.5],
+
+        [-0.5, 0.5, 0.5],
+        [-0.5, 0.5, -0.5],
+        [0.5, 0.5, -0.5],
+        [-0.5, 0.5, 0.5],
+        [0.5, 0.5, -0.5],
+        [0.5, 0.5, 0.5],
+
+        [0.5, -0.5, 0.5],
+        [0.5, 0.5, 0.5],
+        [0.5, 0.5, -0.5],
+        [0.5, -0.5, 0.5],
+        [0.5, 0.5, -0.5],
+        [0.5, -0.5, -0.5],
+
+        [0.5, -0.5, 0.5],
+        [0.5, -0.5, -0.5],
+        [-0.5, -0.5, -0.5],
+        [0.5, -0.5, 0.5],
+        [-0.5, -0.5, -0.5],
+        [-0.5, -0.5, 0.5],
+
+        [0.5, -0.5, -0.5],
+        [0.5, 0.5, -0.5],
+        [-0.5, 0.5, -0.5],
+        [0.5, -0.5, -0.5],
+        [-0.5, 0.5, -0.5],
+        [-0.5, -0.5, -0.5],
+    ];
+
+    let cube_mesh: opengl::Mesh = vertices.as_slice().try_into()?;
+    let window_mesh: opengl::Mesh = vertices[..6]
+        .iter()
+        .map(|[x, y, _]| [*x, *y, 0.0])
+        .collect::<Vec<_>>()
+        .as_slice()
+        .try_into()?;
+
+    let cubes = [
+        glm::translation(&glm::vec3(-1.0, 0.0, -1.0)),
+        glm::translation(&glm::vec3(2.0, 0.0, 0.0)),
+    ];
+    let windows = [
+        ([-1.5f32, 0.0, -0.48], [1.0f32, 0.2, 0.2, 0.5]),
+        ([1.5, 0.0, 0.51], [0.2, 1.0, 0.2, 0.5]),
+        ([0.0, 0.0, 0.7], [0.2, 0.2, 1.0, 0.5]),
+        ([-0.3, 0.0, -2.3], [1.0, 1.0, 0.2, 0.6]),
+        ([0.5, 0.0, -0.6], [0.2, 1.0, 1.0, 0.4]),
+    ];
+
+    let render_state = opengl::RenderState {
+        depth_test: true,
+        ..Default::default()
+    };
+    let mut render_state_cache = opengl::RenderStateCache::new()?;
+    let mut oit = opengl::WeightedBlendedOit::new(1920, 1080)?;
+    let mut use_oit = false;
+
+    let mut camera = camera::Camera::new();
+
+    camera.set_position(&[0.0, 0.0, 3.0]);
+
+    let model_location = shader_program.locate_uniform("model")?;
+    let view_location = shader_program.locate_uniform("view")?;
+    let projection_location = shader_program.locate_uniform("projection")?;
+
+    window.run(
+        |window_size, (_, seconds_since_last_frame), events, input| {
+            camera::process_events(&mut camera, seconds_since_last_frame, events, input);
+            for event in events {
+                if let winman::Event::KeyUp {
+                    keycode: winman::Keycode::O,
+                    ..
+                } = event
+                {
+                    use_oit = !use_oit;
+                }
+            }
+
+            let view = camera.calculate_view();
+            let projection = camera.calculate_projection(window_size);
+
+            let draw_cubes = |render_state_cache: &mut opengl::RenderStateCache| {
+                render_state_cache.apply(&render_state).unwrap();
+                shader_program.enable().unwrap();
+                shader_texture.draw().unwrap();
+                set_mat4(view_location, &view);
+                set_mat4(projection_location, &projection);
+                for model in &cubes {
+                    set_mat4(model_location, model);
+                    cube_mesh.draw(opengl::DrawMode::Triangles).unwrap();
+                }
+            };
+
+            if use_oit {
+                oit.resize(window_size.0, window_size.1).unwrap();
+                oit.begin_opaque(&mut render_state_cache, &render_state)
+                    .unwrap();
+                draw_cubes(&mut render_state_cache);
+
+                oit.begin_transparent(&mut render_state_cache, &render_state)
+                    .unwrap();
+                window_oit_program.enable(&view, &projection).unwrap();
+                for (position, colour) in &windows {
+                    window_oit_program
+                        .draw(&window_mesh, position, colour)
+                        .unwrap();
+                }
+                oit.composite(&mut render_state_cache, &render_state)
+                    .unwrap();
+            } else {
+                draw_cubes(&mut render_state_cache);
+
+                let mut queue = opengl::TransparentQueue::new(&camera.get_position());
+                for (position, colour) in &windows {
+                    queue.push(position, || {
+                        window_program.enable(&view, &projection)?;
+                        window_program.draw(&window_mesh, position, colour)
+                    });
+                }
+                queue.draw(&mut render_state_cache, &render_state).unwrap();
+            }
+        },
+    )
+}
+
+/// Flat coloured quads, drawn either with plain blending or into the OIT targets.
+struct WindowProgram {
+    shader_program: opengl::ShaderProgram,
+    model: opengl::UniformLocation,
+    view: opengl::UniformLocation,
+    projection: opengl::UniformLocation,
+    colour: opengl::UniformLocation,
+}
+
+impl WindowProgram {
+    fn new(fragment_source: &str) -> anyhow::Result<Self> {
+        let shader_program = opengl::ShaderProgram::new(&[
+            opengl::Shader::new(include_str!("shader.vert"), opengl::ShaderType::Vertex)?,
+            opengl::Shader::new(fragment_source, opengl::ShaderType::Fragment)?,
+        ])?;
+
+        Ok(Self {
+            model: shader_program.locate_uniform("model")?,
+            view: shader_program.locate_uniform("view")?,
+            projection: shader_program.locate_uniform("projection")?,
+            colour: shader_program.locate_uniform("window_colour")?,
+            shader_program,
+        })
+    }
+
+    fn enable(&self, view: &glm::Mat4, projection: &glm::Mat4) -> anyhow::Result<()> {
+        self.shader_program.enable()?;
+        opengl_sys::set_uniform_mat4(self.view, false, glm::value_ptr(view))?;
+        opengl_sys::set_uniform_mat4(self.projection, false, glm::value_ptr(projection))?;
+        Ok(())
+    }
+
+    fn draw(
+        &self,
+        mesh: &opengl::Mesh,
+        position: &[f32; 3],
+        colour: &[f32; 4],
+    ) -> anyhow::Result<()> {
+        let model = glm::translation(&glm::make_vec3(position));
+        opengl_sys::set_uniform_mat4(self.model, false, glm::value_ptr(&model))?;
+        opengl_sys::set_uniform_vec4(self.colour, colour)?;
+        mesh.draw(opengl::DrawMode::Triangles)
+    }
+}
+
+fn set_mat4(location: opengl::UniformLocation, mat4: &glm::Mat4) {
+    opengl_sys::set_uniform_mat4(location, false, glm::value_ptr(mat4)).unwrap();
+}
@@ -0,0 +1,9 @@
+#version 330 core
+
+out vec4 colour;
+
+uniform vec4 window_colour;
+
+void main() {
+    colour = window_colour;
+}
\ No newline at end of file
@@ -0,0 +1,12 @@
+#version 330 core
+
+layout (location=0) out vec4 accumulation;
+layout (location=1) out float weight;
+
+uniform vec4 window_colour;
+
+void main() {
+    float w = clamp(0.03 / (1e-5 + pow(gl_FragCoord.z, 4.0)), 1e-2, 3e3);
+    accumulation = vec4(window_colour.rgb * window_colour.a * w, window_colour.a);
+    weight = window_colour.a * w;
+}
\ No newline at end of file
@@ -1,4 +1,4 @@
-use opengl_sys::{Feature, RenderBufferID, TextureID};
+use opengl_sys::{Feature, RenderBufferID};
 
 fn main() -> anyhow::Result<()> {
     let window = winman::Window::new("4.26 Frame Buffers", 1920, 1080)?;
@@ -30,25 +30,19 @@ fn main() -> anyhow::Result<()> {
 
     let frame_buffer1_id = opengl_sys::create_frame_buffer();
     opengl_sys::bind_frame_buffer(frame_buffer1_id, opengl_sys::FrameBufferTarget::All)?;
-    let texture_image1_id = create_render_frame()?;
+    let texture_image1 = opengl::TextureImage2D::new_empty(1920, 1080, opengl::TextureFormat::RGB)?;
     let render_buffer1_id = create_render_buffer()?;
-    {
-        frame_shader_program.enable()?;
-        let location = frame_shader_program.locate_uniform("tex1")?;
-        opengl_sys::set_uniform_i32(location, 0)?;
-    }
+    let frame_shader_texture1 =
+        opengl::ShaderProgramTexture::new(&texture_image1, &frame_shader_program, "tex1")?;
 
     let frame_buffer2_id = opengl_sys::create_frame_buffer();
     opengl_sys::bind_frame_buffer(frame_buffer2_id, opengl_sys::FrameBufferTarget::All)?;
-    let texture_image2_id = create_render_frame()?;
+    let texture_image2 = opengl::TextureImage2D::new_empty(1920, 1080, opengl::TextureFormat::RGB)?;
     let render_buffer2_id = create_render_buffer()?;
-    {
-        frame_shader_program.enable()?;
-        let location = frame_shader_program.locate_uniform("tex2")?;
-        opengl_sys::set_uniform_i32(location, 1)?;
-    }
+    let frame_shader_texture2 =
+        opengl::ShaderProgramTexture::new(&texture_image2, &frame_shader_program, "tex2")?;
 
-    window.run(|_, _, _| {
+    window.run(|_, _, _, _| {
         // ===================== Render Frame Buffers
 
         // ===================== Render First Frame Buffer
@@ -62,14 +56,12 @@ fn main() -> anyhow::Result<()> {
 
         object_shader_program.enable().unwrap();
         opengl_sys::set_uniform_vec3(flavour_location, &[1.0, 0.0, 0.0]).unwrap();
-        opengl_sys::frame_buffer_texture_2d(
-            opengl_sys::FrameBufferTarget::All,
-            opengl_sys::FrameBufferAttachment::Colour(0),
-            opengl_sys::TextureTarget::Image2D,
-            texture_image1_id,
-            0,
-        )
-        .unwrap();
+        texture_image1
+            .attach_to_frame_buffer(
+                opengl::FrameBufferTarget::All,
+                opengl::FrameBufferAttachment::Colour(0),
+            )
+            .unwrap();
         triangle_mesh.draw(opengl::DrawMode::Triangles).unwrap();
 
         // ===================== Render Second Frame Buffer
@@ -84,14 +76,12 @@ fn main() -> anyhow::Result<()> {
 
         object_shader_program.enable().unwrap();
         opengl_sys::set_uniform_vec3(flavour_location, &[1.0, 0.5, 0.0]).unwrap();
-        opengl_sys::frame_buffer_texture_2d(
-            opengl_sys::FrameBufferTarget::All,
-            opengl_sys::FrameBufferAttachment::Colour(0),
-            opengl_sys::TextureTarget::Image2D,
-            texture_image2_id,
-            0,
-        )
-        .unwrap();
+        texture_image2
+            .attach_to_frame_buffer(
+                opengl::FrameBufferTarget::All,
+                opengl::FrameBufferAttachment::Colour(0),
+            )
+            .unwrap();
         triangle_mesh.draw(opengl::DrawMode::Triangles).unwrap();
 
         // ===================== Render to Display
@@ -103,40 +93,12 @@ fn main() -> anyhow::Result<()> {
         opengl_sys::clear(opengl_sys::BufferBit::Colour).unwrap();
 
         frame_shader_program.enable().unwrap();
-        opengl_sys::active_texture(0).unwrap();
-        opengl_sys::bind_texture(texture_image1_id, opengl_sys::TextureTarget::Image2D).unwrap();
-        opengl_sys::active_texture(1).unwrap();
-        opengl_sys::bind_texture(texture_image2_id, opengl_sys::TextureTarget::Image2D).unwrap();
+        frame_shader_texture1.draw().unwrap();
+        frame_shader_texture2.draw().unwrap();
         quad_mesh.draw(opengl::DrawMode::Triangles).unwrap();
     })
 }
 
-fn create_render_frame() -> anyhow::Result<TextureID> {
-    let texture_image_id = opengl_sys::create_texture();
-    opengl_sys::bind_texture(texture_image_id, opengl_sys::TextureTarget::Image2D)?;
-    opengl_sys::load_texture_image2d(
-        opengl_sys::TextureTarget::Image2D,
-        0,
-        opengl_sys::TextureFormat::RGB,
-        1920,
-        1080,
-        opengl_sys::TextureFormat::RGB,
-        opengl_sys::DataType::U32,
-        None as Option<&[()]>,
-    )?;
-    opengl_sys::set_texture_parameter_value(
-        opengl_sys::TextureTarget::Image2D,
-        opengl_sys::TextureParameterName::MinFilter,
-        opengl_sys::TextureParameterValue::Linear,
-    )?;
-    opengl_sys::set_texture_parameter_value(
-        opengl_sys::TextureTarget::Image2D,
-        opengl_sys::TextureParameterName::MagFilter,
-        opengl_sys::TextureParameterValue::Linear,
-    )?;
-    Ok(texture_image_id)
-}
-
 fn create_render_buffer() -> anyhow::Result<RenderBufferID> {
     let render_buffer_id = opengl_sys::create_render_buffer();
     opengl_sys::bind_render_buffer(render_buffer_id)?;
@@ -0,0 +1,236 @@
+use crate::controller::Controllers;
+use crate::{CloseHandle, ClosePolicy, Error, Window};
+
+pub use sdl2::video::{GLProfile, SwapInterval};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum WindowMode {
+    Windowed,
+    /// Windowed without decorations.
+    Borderless,
+    /// Exclusive fullscreen at the requested size, changing the display mode if needed.
+    Fullscreen,
+    /// Covers the desktop at its current resolution.
+    FullscreenDesktop,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CursorMode {
+    /// Hidden and captured, mouse motion is reported relative to the last position.
+    Relative,
+    Visible,
+    Hidden,
+}
+
+pub struct WindowBuilder {
+    title: String,
+    width: u32,
+    height: u32,
+    gl_version: (u8, u8),
+    gl_profile: GLProfile,
+    debug: bool,
+    swap_interval: Option<SwapInterval>,
+    mode: WindowMode,
+    resizable: bool,
+    high_dpi: bool,
+    samples: u8,
+    depth_bits: u8,
+    stencil_bits: u8,
+    cursor_mode: CursorMode,
+    srgb: bool,
+    depth_test: bool,
+    controller_dead_zone: f32,
+    close_policy: ClosePolicy,
+}
+
+impl WindowBuilder {
+    /// Starts from the same configuration as [`Window::new`].
+    pub fn new(title: &str, width: u32, height: u32) -> Self {
+        Self {
+            title: title.to_owned(),
+            width,
+            height,
+            gl_version: (3, 3),
+            gl_profile: GLProfile::Core,
+            debug: false,
+            swap_interval: None,
+            mode: WindowMode::Windowed,
+            resizable: true,
+            high_dpi: false,
+            samples: 0,
+            depth_bits: 24,
+            stencil_bits: 8,
+            cursor_mode: CursorMode::Relative,
+            srgb: false,
+            depth_test: true,
+            controller_dead_zone: 0.15,
+            close_policy: ClosePolicy::CloseRequestedOrEscape,
+        }
+    }
+
+    pub fn gl_version(mut self, major: u8, minor: u8) -> Self {
+        self.gl_version = (major, minor);
+        self
+    }
+
+    pub fn gl_profile(mut self, profile: GLProfile) -> Self {
+        self.gl_profile = profile;
+        self
+    }
+
+    /// Requests a debug context, so drivers report more detailed errors.
+    pub fn debug(mut self, debug: bool) -> Self {
+        self.debug = debug;
+        self
+    }
+
+    /// Leaves the driver default when not set.
+    pub fn swap_interval(mut self, swap_interval: SwapInterval) -> Self {
+        self.swap_interval = Some(swap_interval);
+        self
+    }
+
+    pub fn mode(mut self, mode: WindowMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    pub fn resizable(mut self, resizable: bool) -> Self {
+        self.resizable = resizable;
+        self
+    }
+
+    /// Renders at the display's native resolution, the size passed to the frame callback is
+    /// then in pixels rather than window units.
+    pub fn high_dpi(mut self, high_dpi: bool) -> Self {
+        self.high_dpi = high_dpi;
+        self
+    }
+
+    /// Samples per pixel of the default frame buffer, 0 disables multisampling.
+    pub fn samples(mut self, samples: u8) -> Self {
+        self.samples = samples;
+        self
+    }
+
+    pub fn depth_bits(mut self, depth_bits: u8) -> Self {
+        self.depth_bits = depth_bits;
+        self
+    }
+
+    pub fn stencil_bits(mut self, stencil_bits: u8) -> Self {
+        self.stencil_bits = stencil_bits;
+        self
+    }
+
+    pub fn cursor_mode(mut self, cursor_mode: CursorMode) -> Self {
+        self.cursor_mode = cursor_mode;
+        self
+    }
+
+    /// Converts linear fragment colours to sRGB when writing to the default frame buffer.
+    pub fn srgb(mut self, srgb: bool) -> Self {
+        self.srgb = srgb;
+        self
+    }
+
+    /// Whether depth testing starts enabled.
+    pub fn depth_test(mut self, depth_test: bool) -> Self {
+        self.depth_test = depth_test;
+        self
+    }
+
+    /// Fraction of each controller axis' range, around rest, that is reported as 0.
+    pub fn controller_dead_zone(mut self, dead_zone: f32) -> Self {
+        self.controller_dead_zone = dead_zone;
+        self
+    }
+
+    pub fn close_policy(mut self, close_policy: ClosePolicy) -> Self {
+        self.close_policy = close_policy;
+        self
+    }
+
+    pub fn build(self) -> anyhow::Result<Window> {
+        let sdl = sdl2::init().map_err(Error::SDL)?;
+        let video = sdl.video().map_err(Error::SDL)?;
+        {
+            let gl_attr = video.gl_attr();
+            gl_attr.set_context_profile(self.gl_profile);
+            gl_attr.set_context_version(self.gl_version.0, self.gl_version.1);
+            if self.debug {
+                gl_attr.set_context_flags().debug().set();
+            }
+            gl_attr.set_depth_size(self.depth_bits);
+            gl_attr.set_stencil_size(self.stencil_bits);
+            gl_attr.set_multisample_buffers(u8::from(self.samples > 0));
+            gl_attr.set_multisample_samples(self.samples);
+            gl_attr.set_framebuffer_srgb_compatible(self.srgb);
+        }
+
+        // Controllers are optional, so a missing subsystem shouldn't stop the window opening
+        let controllers = sdl
+            .game_controller()
+            .ok()
+            .map(|subsystem| Controllers::new(subsystem, self.controller_dead_zone));
+
+        let mouse = sdl.mouse();
+        mouse.set_relative_mouse_mode(self.cursor_mode == CursorMode::Relative);
+        mouse.show_cursor(self.cursor_mode == CursorMode::Visible);
+
+        let mut window_builder = video.window(&self.title, self.width, self.height);
+        window_builder.opengl();
+        if self.resizable {
+            window_builder.resizable();
+        }
+        if self.high_dpi {
+            window_builder.allow_highdpi();
+        }
+        match self.mode {
+            WindowMode::Windowed => {}
+            WindowMode::Borderless => {
+                window_builder.borderless();
+            }
+            WindowMode::Fullscreen => {
+                window_builder.fullscreen();
+            }
+            WindowMode::FullscreenDesktop => {
+                window_builder.fullscreen_desktop();
+            }
+        }
+        let window = window_builder.build()?;
+
+        let gl_context = window.gl_create_context().map_err(Error::GL)?;
+        gl::load_with(|s| video.gl_get_proc_address(s) as *const std::os::raw::c_void);
+        if let Some(swap_interval) = self.swap_interval {
+            video
+                .gl_set_swap_interval(swap_interval)
+                .map_err(Error::SDL)?;
+        }
+
+        let (width, height) = window.drawable_size();
+        unsafe {
+            gl::Viewport(0, 0, width as _, height as _);
+            if self.depth_test {
+                gl::Enable(gl::DEPTH_TEST);
+            }
+            if self.samples > 0 {
+                gl::Enable(gl::MULTISAMPLE);
+            }
+            if self.srgb {
+                gl::Enable(gl::FRAMEBUFFER_SRGB);
+            }
+        }
+
+        Ok(Window {
+            sdl,
+            window,
+            _video: video,
+            _gl_context: gl_context,
+            controllers,
+            close_policy: self.close_policy,
+            close_handle: CloseHandle::default(),
+            cursor_mode: self.cursor_mode,
+        })
+    }
+}
@@ -0,0 +1,119 @@
+use crate::input::*;
+use sdl2::controller::GameController;
+use sdl2::GameControllerSubsystem;
+use std::collections::HashMap;
+
+pub use sdl2::controller::{Axis as ControllerAxis, Button as ControllerButton};
+
+type SdlEvent = sdl2::event::Event;
+
+/// Identifies a connected controller until it is disconnected.
+pub type ControllerID = u32;
+
+/// Opens controllers as they are plugged in and turns their raw events into [`Event`]s.
+pub(crate) struct Controllers {
+    subsystem: GameControllerSubsystem,
+    open: HashMap<ControllerID, GameController>,
+    axes: HashMap<(ControllerID, ControllerAxis), f32>,
+    dead_zone: f32,
+}
+
+impl Controllers {
+    pub(crate) fn new(subsystem: GameControllerSubsystem, dead_zone: f32) -> Self {
+        Self {
+            subsystem,
+            open: Default::default(),
+            axes: Default::default(),
+            dead_zone: dead_zone.clamp(0.0, 0.99),
+        }
+    }
+
+    /// Returns `None` for events that aren't from a controller, or that don't change anything
+    /// once the dead zone has been applied.
+    pub(crate) fn handle(&mut self, event: &SdlEvent) -> Option<Event> {
+        match *event {
+            SdlEvent::ControllerDeviceAdded { which, .. } => {
+                let controller = self.subsystem.open(which).ok()?;
+                let id = controller.instance_id();
+                // Controllers present at startup can be reported more than once
+                if self.open.contains_key(&id) {
+                    return None;
+                }
+                let name = controller.name();
+                self.open.insert(id, controller);
+                Some(Event::ControllerConnected { id, name })
+            }
+            SdlEvent::ControllerDeviceRemoved { which, .. } => {
+                self.open.remove(&which)?;
+                self.axes.retain(|(id, _), _| *id != which);
+                Some(Event::ControllerDisconnected(which))
+            }
+            SdlEvent::ControllerButtonDown { which, button, .. } => {
+                Some(Event::ControllerButtonDown { id: which, button })
+            }
+            SdlEvent::ControllerButtonUp { which, button, .. } => {
+                Some(Event::ControllerButtonUp { id: which, button })
+            }
+            SdlEvent::ControllerAxisMotion {
+                which, axis, value, ..
+            } => {
+                let value = apply_dead_zone(value as f32 / i16::MAX as f32, self.dead_zone);
+                let previous = self.axes.insert((which, axis), value).unwrap_or(0.0);
+                (previous != value).then_some(Event::ControllerAxis {
+                    id: which,
+                    axis,
+                    value,
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Zeroes small deflections and rescales the rest so the output still starts at 0 and
+/// reaches ±1.
+fn apply_dead_zone(value: f32, dead_zone: f32) -> f32 {
+    let value = value.clamp(-1.0, 1.0);
+    if value.abs() < dead_zone {
+        0.0
+    } else {
+        value.signum() * (value.abs() - dead_zone) / (1.0 - dead_zone)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn small_deflections_are_zeroed() {
+        for value in [0.0, 0.1, -0.1, 0.249, -0.249] {
+            assert_eq!(apply_dead_zone(value, 0.25), 0.0);
+        }
+    }
+
+    #[test]
+    fn output_is_continuous_at_the_edge() {
+        for sign in [1.0, -1.0] {
+            let just_outside = apply_dead_zone(sign * (0.25 + 1e-5), 0.25);
+            assert!(just_outside.abs() < 1e-4, "{just_outside}");
+            assert_eq!(just_outside.signum(), sign);
+        }
+    }
+
+    #[test]
+    fn full_deflection_reaches_one() {
+        for dead_zone in [0.0, 0.25, 0.9] {
+            assert!((apply_dead_zone(1.0, dead_zone) - 1.0).abs() < 1e-6);
+            assert!((apply_dead_zone(-1.0, dead_zone) + 1.0).abs() < 1e-6);
+            // Out of range input is clamped
+            assert!((apply_dead_zone(1.5, dead_zone) - 1.0).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn output_is_rescaled_linearly() {
+        assert!((apply_dead_zone(0.625, 0.25) - 0.5).abs() < 1e-6);
+        assert!((apply_dead_zone(-0.625, 0.25) + 0.5).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,303 @@
+use crate::builder::*;
+use crate::controller::*;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+pub use sdl2::keyboard::Keycode;
+pub use sdl2::mouse::MouseButton;
+
+use sdl2::keyboard::Mod;
+use sdl2::mouse::MouseWheelDirection;
+
+type SdlEvent = sdl2::event::Event;
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Modifiers {
+    pub shift: bool,
+    pub ctrl: bool,
+    pub alt: bool,
+    /// The Windows, Command or Super key.
+    pub logo: bool,
+}
+
+impl From<Mod> for Modifiers {
+    fn from(value: Mod) -> Self {
+        Modifiers {
+            shift: value.intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD),
+            ctrl: value.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD),
+            alt: value.intersects(Mod::LALTMOD | Mod::RALTMOD),
+            logo: value.intersects(Mod::LGUIMOD | Mod::RGUIMOD),
+        }
+    }
+}
+
+/// Cursor positions are in window coordinates with the origin at the top left.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Event {
+    KeyDown {
+        keycode: Keycode,
+        modifiers: Modifiers,
+        /// Generated by the OS while the key is held rather than by a new press.
+        repeat: bool,
+    },
+    KeyUp {
+        keycode: Keycode,
+        modifiers: Modifiers,
+    },
+    /// Characters typed, after keyboard layout and IME composition.
+    TextInput(String),
+    MouseMotion {
+        x: f32,
+        y: f32,
+        x_rel: f32,
+        y_rel: f32,
+    },
+    MouseButtonDown {
+        button: MouseButton,
+        x: f32,
+        y: f32,
+        clicks: u8,
+    },
+    MouseButtonUp {
+        button: MouseButton,
+        x: f32,
+        y: f32,
+    },
+    /// Positive `y` scrolls away from the user, positive `x` to the right.
+    MouseScroll {
+        x: f32,
+        y: f32,
+    },
+    /// The user asked to close the window, e.g. through its close button.
+    CloseRequested,
+    FocusGained,
+    FocusLost,
+    /// New size of the drawable area in pixels.
+    Resized(u32, u32),
+    FileDropped(PathBuf),
+    ControllerConnected {
+        id: ControllerID,
+        name: String,
+    },
+    ControllerDisconnected(ControllerID),
+    ControllerButtonDown {
+        id: ControllerID,
+        button: ControllerButton,
+    },
+    ControllerButtonUp {
+        id: ControllerID,
+        button: ControllerButton,
+    },
+    /// Sticks range from -1 to 1 with positive `y` pointing down, triggers from 0 to 1.
+    /// Values inside the dead zone are reported as 0.
+    ControllerAxis {
+        id: ControllerID,
+        axis: ControllerAxis,
+        value: f32,
+    },
+}
+
+impl Event {
+    /// Translates the events apps care about, window resizes are handled by the caller since
+    /// they need the drawable size.
+    pub(crate) fn from_sdl(event: SdlEvent) -> Option<Self> {
+        use sdl2::event::WindowEvent;
+
+        let event = match event {
+            SdlEvent::KeyDown {
+                keycode: Some(keycode),
+                keymod,
+                repeat,
+                ..
+            } => Event::KeyDown {
+                keycode,
+                modifiers: keymod.into(),
+                repeat,
+            },
+            SdlEvent::KeyUp {
+                keycode: Some(keycode),
+                keymod,
+                ..
+            } => Event::KeyUp {
+                keycode,
+                modifiers: keymod.into(),
+            },
+            SdlEvent::TextInput { text, .. } => Event::TextInput(text),
+            SdlEvent::MouseMotion {
+                x, y, xrel, yrel, ..
+            } => Event::MouseMotion {
+                x: x as f32,
+                y: y as f32,
+                x_rel: xrel as f32,
+                y_rel: yrel as f32,
+            },
+            SdlEvent::MouseButtonDown {
+                mouse_btn,
+                clicks,
+                x,
+                y,
+                ..
+            } => Event::MouseButtonDown {
+                button: mouse_btn,
+                x: x as f32,
+                y: y as f32,
+                clicks,
+            },
+            SdlEvent::MouseButtonUp {
+                mouse_btn, x, y, ..
+            } => Event::MouseButtonUp {
+                button: mouse_btn,
+                x: x as f32,
+                y: y as f32,
+            },
+            SdlEvent::MouseWheel {
+                x, y, direction, ..
+            } => {
+                let sign = match direction {
+                    MouseWheelDirection::Flipped => -1.0,
+                    _ => 1.0,
+                };
+                Event::MouseScroll {
+                    x: sign * x as f32,
+                    y: sign * y as f32,
+                }
+            }
+            SdlEvent::Window {
+                win_event: WindowEvent::FocusGained,
+                ..
+            } => Event::FocusGained,
+            SdlEvent::Window {
+                win_event: WindowEvent::FocusLost,
+                ..
+            } => Event::FocusLost,
+            SdlEvent::DropFile { filename, .. } => Event::FileDropped(filename.into()),
+            _ => return None,
+        };
+        Some(event)
+    }
+}
+
+/// Snapshot of what is held down once the frame's events have been applied, so it always
+/// agrees with the events the frame callback receives.
+#[derive(Clone, Debug, Default)]
+pub struct InputState {
+    keys_held: HashSet<Keycode>,
+    buttons_held: HashSet<MouseButton>,
+    cursor_position: (f32, f32),
+    modifiers: Modifiers,
+    controllers: Vec<ControllerID>,
+    controller_buttons_held: HashSet<(ControllerID, ControllerButton)>,
+    controller_axes: HashMap<(ControllerID, ControllerAxis), f32>,
+    /// Set by the window each frame, `None` until then.
+    pixel_scale: Option<(f32, f32)>,
+    cursor_mode: Option<CursorMode>,
+}
+
+impl InputState {
+    pub fn is_key_held(&self, keycode: Keycode) -> bool {
+        self.keys_held.contains(&keycode)
+    }
+
+    pub fn keys_held(&self) -> impl Iterator<Item = Keycode> + '_ {
+        self.keys_held.iter().copied()
+    }
+
+    pub fn is_button_held(&self, button: MouseButton) -> bool {
+        self.buttons_held.contains(&button)
+    }
+
+    /// In window coordinates, like the positions in mouse events.
+    pub fn cursor_position(&self) -> (f32, f32) {
+        self.cursor_position
+    }
+
+    /// Drawable pixels per window coordinate, above 1 on high-DPI displays.
+    pub fn pixel_scale(&self) -> (f32, f32) {
+        self.pixel_scale.unwrap_or((1.0, 1.0))
+    }
+
+    /// Converts a mouse position or motion from window coordinates to the pixels the frame
+    /// callback's window size is given in.
+    pub fn to_pixels(&self, (x, y): (f32, f32)) -> (f32, f32) {
+        let (scale_x, scale_y) = self.pixel_scale();
+        (x * scale_x, y * scale_y)
+    }
+
+    /// Under [`CursorMode::Relative`] only the relative motion in mouse events is meaningful.
+    pub fn cursor_mode(&self) -> CursorMode {
+        self.cursor_mode.unwrap_or(CursorMode::Relative)
+    }
+
+    pub fn modifiers(&self) -> Modifiers {
+        self.modifiers
+    }
+
+    /// Connected controllers, in the order they were connected.
+    pub fn controllers(&self) -> &[ControllerID] {
+        &self.controllers
+    }
+
+    pub fn is_controller_button_held(&self, id: ControllerID, button: ControllerButton) -> bool {
+        self.controller_buttons_held.contains(&(id, button))
+    }
+
+    pub fn controller_axis(&self, id: ControllerID, axis: ControllerAxis) -> f32 {
+        self.controller_axes
+            .get(&(id, axis))
+            .copied()
+            .unwrap_or(0.0)
+    }
+
+    pub(crate) fn set_window(&mut self, pixel_scale: (f32, f32), cursor_mode: CursorMode) {
+        self.pixel_scale = Some(pixel_scale);
+        self.cursor_mode = Some(cursor_mode);
+    }
+
+    pub(crate) fn update(&mut self, event: &Event) {
+        match event {
+            Event::KeyDown {
+                keycode, modifiers, ..
+            } => {
+                self.keys_held.insert(*keycode);
+                self.modifiers = *modifiers;
+            }
+            Event::KeyUp { keycode, modifiers } => {
+                self.keys_held.remove(keycode);
+                self.modifiers = *modifiers;
+            }
+            Event::MouseMotion { x, y, .. } => self.cursor_position = (*x, *y),
+            Event::MouseButtonDown { button, x, y, .. } => {
+                self.buttons_held.insert(*button);
+                self.cursor_position = (*x, *y);
+            }
+            Event::MouseButtonUp { button, x, y } => {
+                self.buttons_held.remove(button);
+                self.cursor_position = (*x, *y);
+            }
+            // Releases that happen while unfocused are never reported
+            Event::FocusLost => {
+                self.keys_held.clear();
+                self.buttons_held.clear();
+                self.modifiers = Modifiers::default();
+            }
+            Event::ControllerConnected { id, .. } => self.controllers.push(*id),
+            Event::ControllerDisconnected(id) => {
+                self.controllers.retain(|controller| controller != id);
+                self.controller_buttons_held
+                    .retain(|(controller, _)| controller != id);
+                self.controller_axes
+                    .retain(|(controller, _), _| controller != id);
+            }
+            Event::ControllerButtonDown { id, button } => {
+                self.controller_buttons_held.insert((*id, *button));
+            }
+            Event::ControllerButtonUp { id, button } => {
+                self.controller_buttons_held.remove(&(*id, *button));
+            }
+            Event::ControllerAxis { id, axis, value } => {
+                self.controller_axes.insert((*id, *axis), *value);
+            }
+            _ => {}
+        }
+    }
+}
@@ -1,6 +1,17 @@
+mod builder;
+mod controller;
+mod input;
+
+pub use builder::*;
+pub use controller::*;
+pub use input::*;
+
 use sdl2::event::WindowEvent;
 use sdl2::video::GLContext;
-use sdl2::{Sdl, VideoSubsystem};
+use sdl2::{EventPump, Sdl, VideoSubsystem};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 type VideoWindow = sdl2::video::Window;
 type SdlEvent = sdl2::event::Event;
 
@@ -12,93 +23,132 @@ pub enum Error {
     GL(String),
 }
 
+/// Returned from the frame callback, `()` converts to [`ControlFlow::Continue`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ControlFlow {
+    Continue,
+    Exit,
+}
+
+impl From<()> for ControlFlow {
+    fn from(_: ()) -> Self {
+        ControlFlow::Continue
+    }
+}
+
+/// When [`Window::run`] stops on its own, besides the callback returning [`ControlFlow::Exit`]
+/// or a close being requested through [`CloseHandle`]. The callback still sees the event that
+/// caused it, during the last frame.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ClosePolicy {
+    /// On [`Event::CloseRequested`] or when Escape is released.
+    CloseRequestedOrEscape,
+    /// On [`Event::CloseRequested`].
+    CloseRequested,
+    /// Never, the callback decides.
+    Manual,
+}
+
+/// Asks a running window to stop after the current frame, can be sent to other threads.
+#[derive(Clone, Debug, Default)]
+pub struct CloseHandle {
+    requested: Arc<AtomicBool>,
+}
+
+impl CloseHandle {
+    pub fn request_close(&self) {
+        self.requested.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_close_requested(&self) -> bool {
+        self.requested.load(Ordering::Relaxed)
+    }
+}
+
+/// Update rate for [`Window::run_fixed`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct FixedTimestep {
+    pub step: Duration,
+    /// Updates run at most this many times per frame, whole steps beyond that are dropped. 0
+    /// is treated as 1.
+    pub max_steps_per_frame: u32,
+}
+
+impl FixedTimestep {
+    pub fn per_second(updates_per_second: u32) -> Self {
+        FixedTimestep {
+            step: Duration::from_secs(1) / updates_per_second.max(1),
+            max_steps_per_frame: 8,
+        }
+    }
+
+    /// Adds a frame's `elapsed` time to `accumulated` and takes out the updates it pays for.
+    /// Returns how many updates to run and how far the leftover time is towards the next one,
+    /// from 0 up to but not including 1. A zero `step` is treated as 1 nanosecond.
+    pub fn advance(&self, accumulated: &mut Duration, elapsed: Duration) -> (u32, f32) {
+        let step = self.step.max(Duration::from_nanos(1));
+        let max_steps = self.max_steps_per_frame.max(1);
+
+        *accumulated += elapsed;
+        let steps = (accumulated.as_nanos() / step.as_nanos()).min(max_steps as u128) as u32;
+        *accumulated -= step * steps;
+        if steps == max_steps {
+            // Too far behind to catch up, so let the simulation slow down instead
+            let remainder = accumulated.as_nanos() % step.as_nanos();
+            *accumulated = Duration::from_nanos(remainder as u64);
+        }
+
+        let alpha = accumulated.as_secs_f64() / step.as_secs_f64();
+        (steps, (alpha as f32).min(1.0f32.next_down()))
+    }
+}
+
 pub struct Window {
     sdl: Sdl,
     window: VideoWindow,
     _video: VideoSubsystem,
     _gl_context: GLContext,
-}
-
-pub use sdl2::keyboard::Keycode;
-pub enum Event {
-    KeyUp(Keycode),
-    KeyDown(Keycode),
-    MousePosition(f32, f32),
-    MouseScroll(f32),
+    /// `None` when the controller subsystem couldn't be initialised.
+    controllers: Option<controller::Controllers>,
+    close_policy: ClosePolicy,
+    close_handle: CloseHandle,
+    cursor_mode: CursorMode,
 }
 
 impl Window {
+    /// A resizable GL 3.3 core window with relative mouse mode, see [`WindowBuilder`] for more.
     pub fn new(title: &str, width: u32, height: u32) -> anyhow::Result<Self> {
-        let sdl = sdl2::init().map_err(Error::SDL)?;
-        let video = sdl.video().map_err(Error::SDL)?;
-        {
-            let gl_attr = video.gl_attr();
-            gl_attr.set_context_profile(sdl2::video::GLProfile::Core);
-            gl_attr.set_context_version(3, 3);
-        }
-        sdl.mouse().set_relative_mouse_mode(true);
-        let window = video
-            .window(title, width, height)
-            .opengl()
-            .resizable()
-            .build()?;
-
-        let gl_context = window.gl_create_context().map_err(Error::GL)?;
-        gl::load_with(|s| video.gl_get_proc_address(s) as *const std::os::raw::c_void);
-        unsafe { gl::Enable(gl::DEPTH_TEST) };
-
-        Ok(Window {
-            sdl,
-            window,
-            _video: video,
-            _gl_context: gl_context,
-        })
-    }
-
-    pub fn run<F>(self, mut game_logic: F) -> anyhow::Result<()>
+        WindowBuilder::new(title, width, height).build()
+    }
+
+    /// Stops [`Window::run`] after the current frame, regardless of the [`ClosePolicy`].
+    pub fn request_close(&self) {
+        self.close_handle.request_close();
+    }
+
+    /// For requesting a close from inside the frame callback or another thread.
+    pub fn close_handle(&self) -> CloseHandle {
+        self.close_handle.clone()
+    }
+
+    pub fn run<F, R>(mut self, mut game_logic: F) -> anyhow::Result<()>
     where
-        F: FnMut((u32, u32), (f32, f32), &[Event]),
+        F: FnMut((u32, u32), (f32, f32), &[Event], &InputState) -> R,
+        R: Into<ControlFlow>,
     {
         let mut events = Vec::with_capacity(50);
+        let mut input_state = InputState::default();
         let mut event_pump = self.sdl.event_pump().map_err(Error::SDL)?;
 
-        let start_instant = std::time::Instant::now();
+        let start_instant = Instant::now();
         let mut last_frame_instant = start_instant;
 
-        'main: loop {
+        while !self.close_handle.is_close_requested() {
             events.clear();
-            for event in event_pump.poll_iter() {
-                match event {
-                    SdlEvent::Quit { .. }
-                    | SdlEvent::KeyUp {
-                        keycode: Some(Keycode::Escape),
-                        ..
-                    } => break 'main,
-                    SdlEvent::Window {
-                        win_event: WindowEvent::Resized(width, height),
-                        ..
-                    } => unsafe {
-                        gl::Viewport(0, 0, width, height);
-                    },
-                    SdlEvent::KeyUp {
-                        keycode: Some(keycode),
-                        ..
-                    } => events.push(Event::KeyUp(keycode)),
-                    SdlEvent::KeyDown {
-                        keycode: Some(keycode),
-                        ..
-                    } => events.push(Event::KeyDown(keycode)),
-                    SdlEvent::MouseMotion { xrel, yrel, .. } => {
-                        events.push(Event::MousePosition(xrel as f32, yrel as f32))
-                    }
-                    SdlEvent::MouseWheel { y, .. } => events.push(Event::MouseScroll(y as f32)),
-                    _ => {}
-                }
-            }
-
-            unsafe { gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT) };
+            self.poll_events(&mut event_pump, &mut events, &mut input_state);
+            clear_frame();
 
-            let current_frame_instant = std::time::Instant::now();
+            let current_frame_instant = Instant::now();
             let seconds_since_last_frame = current_frame_instant
                 .duration_since(last_frame_instant)
                 .as_secs_f32();
@@ -106,15 +156,219 @@ impl Window {
                 .duration_since(start_instant)
                 .as_secs_f32();
 
-            game_logic(
-                self.window.size(),
+            let control_flow = game_logic(
+                self.window.drawable_size(),
                 (total_passed_seconds, seconds_since_last_frame),
                 &events,
+                &input_state,
             );
+            self.handle_control_flow(control_flow.into());
             self.window.gl_swap_window();
 
             last_frame_instant = current_frame_instant;
         }
         Ok(())
     }
+
+    /// Runs `update` at a fixed rate, independent of the frame rate, and `render` once per frame.
+    ///
+    /// `update` receives the step length in seconds, events are handed to the first update of
+    /// the next frame that has one. `render` receives how far the current time is between the
+    /// last update and the next one, from 0 up to 1, for interpolating what is drawn. Both share
+    /// `state`, so a simulation can also be stepped without a window.
+    pub fn run_fixed<S, U, R, UpdateFlow, RenderFlow>(
+        mut self,
+        timestep: FixedTimestep,
+        state: &mut S,
+        mut update: U,
+        mut render: R,
+    ) -> anyhow::Result<()>
+    where
+        U: FnMut(&mut S, f32, &[Event], &InputState) -> UpdateFlow,
+        R: FnMut(&mut S, (u32, u32), f32, &InputState) -> RenderFlow,
+        UpdateFlow: Into<ControlFlow>,
+        RenderFlow: Into<ControlFlow>,
+    {
+        let mut events = Vec::with_capacity(50);
+        let mut input_state = InputState::default();
+        let mut event_pump = self.sdl.event_pump().map_err(Error::SDL)?;
+
+        let mut last_frame_instant = Instant::now();
+        let mut accumulated = Duration::ZERO;
+
+        while !self.close_handle.is_close_requested() {
+            self.poll_events(&mut event_pump, &mut events, &mut input_state);
+
+            let current_frame_instant = Instant::now();
+            let elapsed = current_frame_instant.duration_since(last_frame_instant);
+            last_frame_instant = current_frame_instant;
+
+            let (steps, alpha) = timestep.advance(&mut accumulated, elapsed);
+            for _ in 0..steps {
+                let control_flow =
+                    update(state, timestep.step.as_secs_f32(), &events, &input_state);
+                self.handle_control_flow(control_flow.into());
+                events.clear();
+            }
+
+            clear_frame();
+            let control_flow = render(state, self.window.drawable_size(), alpha, &input_state);
+            self.handle_control_flow(control_flow.into());
+            self.window.gl_swap_window();
+        }
+        Ok(())
+    }
+
+    /// Appends this frame's events and applies them to `input_state`.
+    fn poll_events(
+        &mut self,
+        event_pump: &mut EventPump,
+        events: &mut Vec<Event>,
+        input_state: &mut InputState,
+    ) {
+        let first_new = events.len();
+        for event in event_pump.poll_iter() {
+            match event {
+                SdlEvent::Quit { .. } => events.push(Event::CloseRequested),
+                SdlEvent::Window {
+                    win_event: WindowEvent::SizeChanged(..),
+                    ..
+                } => {
+                    // The drawable can be larger than the window on high-DPI displays
+                    let (width, height) = self.window.drawable_size();
+                    unsafe { gl::Viewport(0, 0, width as _, height as _) };
+                    events.push(Event::Resized(width, height));
+                }
+                event => events.extend(
+                    self.controllers
+                        .as_mut()
+                        .and_then(|controllers| controllers.handle(&event))
+                        .or_else(|| Event::from_sdl(event)),
+                ),
+            }
+        }
+        let (window_width, window_height) = self.window.size();
+        let (drawable_width, drawable_height) = self.window.drawable_size();
+        let pixel_scale = (
+            drawable_width as f32 / window_width.max(1) as f32,
+            drawable_height as f32 / window_height.max(1) as f32,
+        );
+        input_state.set_window(pixel_scale, self.cursor_mode);
+        for event in &events[first_new..] {
+            input_state.update(event);
+            if self.closes_on(event) {
+                self.close_handle.request_close();
+            }
+        }
+    }
+
+    fn handle_control_flow(&self, control_flow: ControlFlow) {
+        if control_flow == ControlFlow::Exit {
+            self.close_handle.request_close();
+        }
+    }
+
+    fn closes_on(&self, event: &Event) -> bool {
+        match (self.close_policy, event) {
+            (ClosePolicy::Manual, _) => false,
+            (_, Event::CloseRequested) => true,
+            (
+                ClosePolicy::CloseRequestedOrEscape,
+                Event::KeyUp {
+                    keycode: Keycode::Escape,
+                    ..
+                },
+            ) => true,
+            _ => false,
+        }
+    }
+}
+
+fn clear_frame() {
+    unsafe { gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT | gl::STENCIL_BUFFER_BIT) };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timestep(max_steps_per_frame: u32) -> FixedTimestep {
+        FixedTimestep {
+            step: Duration::from_millis(10),
+            max_steps_per_frame,
+        }
+    }
+
+    #[test]
+    fn frames_run_the_steps_they_pay_for() {
+        let mut accumulated = Duration::ZERO;
+        let (steps, alpha) = timestep(8).advance(&mut accumulated, Duration::from_millis(25));
+        assert_eq!(steps, 2);
+        assert_eq!(accumulated, Duration::from_millis(5));
+        assert!((alpha - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn short_frames_carry_their_time_to_the_next() {
+        let timestep = timestep(8);
+        let mut accumulated = Duration::ZERO;
+        assert_eq!(
+            timestep
+                .advance(&mut accumulated, Duration::from_millis(4))
+                .0,
+            0
+        );
+        assert_eq!(
+            timestep
+                .advance(&mut accumulated, Duration::from_millis(4))
+                .0,
+            0
+        );
+        assert_eq!(
+            timestep
+                .advance(&mut accumulated, Duration::from_millis(4))
+                .0,
+            1
+        );
+        assert_eq!(accumulated, Duration::from_millis(2));
+    }
+
+    #[test]
+    fn slow_frames_are_clamped() {
+        let mut accumulated = Duration::ZERO;
+        let (steps, alpha) = timestep(3).advance(&mut accumulated, Duration::from_millis(1007));
+        assert_eq!(steps, 3);
+        // Whole steps are dropped, the fraction of one is kept
+        assert_eq!(accumulated, Duration::from_millis(7));
+        assert!((alpha - 0.7).abs() < 1e-6);
+    }
+
+    #[test]
+    fn zero_max_steps_is_treated_as_one() {
+        let mut accumulated = Duration::ZERO;
+        let (steps, _) = timestep(0).advance(&mut accumulated, Duration::from_millis(35));
+        assert_eq!(steps, 1);
+        assert_eq!(accumulated, Duration::from_millis(5));
+    }
+
+    #[test]
+    fn alpha_stays_below_one() {
+        let timestep = timestep(8);
+        let mut accumulated = Duration::ZERO;
+        for nanos in [0, 1, 9_999_999, 10_000_000, 123_456_789, 10_000_000 * 8 - 1] {
+            let (_, alpha) = timestep.advance(&mut accumulated, Duration::from_nanos(nanos));
+            assert!((0.0..1.0).contains(&alpha), "{alpha}");
+        }
+    }
+
+    #[test]
+    fn same_frame_times_give_the_same_steps() {
+        let frames = [16, 17, 33, 5, 1, 50, 16].map(Duration::from_millis);
+        let run = || {
+            let mut accumulated = Duration::ZERO;
+            frames.map(|elapsed| timestep(4).advance(&mut accumulated, elapsed).0)
+        };
+        assert_eq!(run(), [1, 2, 3, 1, 0, 4, 1]);
+        assert_eq!(run(), run());
+    }
 }